@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+/*!
+* # Quick Timer Macros
+*
+* > Attribute macros for [quick-timer](https://crates.io/crates/quick-timer).
+*
+* Use these through the `macros` feature of `quick-timer` rather than depending
+* on this crate directly:
+*
+* ```toml
+* [dependencies]
+* quick-timer = { version = "0.1.2", features = ["macros"] }
+* ```
+*/
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Times every call of a function, the same way wrapping its body in `timer!` would.
+///
+/// The default tag is the path of the function, e.g. `my_crate::db::load`. A custom
+/// tag can be given as a string literal, raw or not: `#[timed("load")]`.
+///
+/// `#[timed]` can be put on free functions and methods, including async ones, which
+/// are timed like `timer_async!`. When put on an `impl`
+/// block, it times every method in the block, and the default tags include the
+/// type name, e.g. `my_crate::db::Connection::load`. An attribute on a single
+/// method cannot see the `impl` block around it, so the default tag of such a
+/// method leaves out the type name, e.g. `my_crate::db::load`; put `#[timed]` on
+/// the `impl` block, or give the method a tag, to tell the methods of different
+/// types apart.
+///
+/// Like `timer!`, the instrumentation is only compiled in debug builds, unless
+/// the `release_also` feature of `quick-timer` is enabled.
+///
+/// The generated code refers to `quick-timer` as `::quick_timer`, so renaming
+/// the dependency in `Cargo.toml` is not supported.
+///
+/// # Examples
+///
+/// ```ignore
+/// use quick_timer::timed;
+///
+/// #[timed]
+/// fn load() -> Vec<u8> {
+///     std::fs::read("data.bin").unwrap_or_default()
+/// }
+///
+/// struct Parser;
+///
+/// #[timed]
+/// impl Parser {
+///     fn parse(&self, input: &[u8]) -> usize {
+///         input.len()
+///     }
+///
+///     #[timed("validate input")]
+///     fn validate(&self, input: &[u8]) -> bool {
+///         !input.is_empty()
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn timed(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let tag = match parse_tag(attr) {
+        Ok(tag) => tag,
+        Err(error) => return error,
+    };
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+    let result = match find_keyword(&tokens) {
+        Some((index, "impl")) => instrument_impl(&mut tokens, index, tag),
+        Some((index, "fn")) => instrument_fn(&mut tokens, index, tag, None),
+        _ => Err(error(
+            Span::call_site(),
+            "#[timed] can only be used on functions and impl blocks",
+        )),
+    };
+    match result {
+        Ok(()) => tokens.into_iter().collect(),
+        Err(error) => error,
+    }
+}
+
+/// Parses the attribute arguments: nothing, or a single string literal.
+fn parse_tag(attr: TokenStream) -> Result<Option<Literal>, TokenStream> {
+    let mut attr = attr.into_iter();
+    match (attr.next(), attr.next()) {
+        (None, _) => Ok(None),
+        (Some(TokenTree::Literal(tag)), None) if is_string_literal(&tag) => Ok(Some(tag)),
+        (Some(token), _) => Err(error(
+            token.span(),
+            "expected a string literal tag, e.g. #[timed(\"my tag\")]",
+        )),
+    }
+}
+
+/// Finds the `fn` or `impl` keyword that starts the item, skipping attributes,
+/// visibility and qualifiers.
+fn find_keyword(tokens: &[TokenTree]) -> Option<(usize, &'static str)> {
+    tokens
+        .iter()
+        .enumerate()
+        .find_map(|(index, token)| match token {
+            TokenTree::Ident(ident) if ident.to_string() == "fn" => Some((index, "fn")),
+            TokenTree::Ident(ident) if ident.to_string() == "impl" => Some((index, "impl")),
+            _ => None,
+        })
+}
+
+/// Wraps the body of the function whose `fn` keyword is at `fn_index`.
+///
+/// `self_ty` is the name of the type when the function is a method inside an
+/// instrumented `impl` block.
+fn instrument_fn(
+    tokens: &mut [TokenTree],
+    fn_index: usize,
+    tag: Option<Literal>,
+    self_ty: Option<&str>,
+) -> Result<(), TokenStream> {
+    let is_async = tokens[..fn_index].iter().any(|t| is_ident(t, "async"));
+    let name = match tokens.get(fn_index + 1) {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        _ => return Err(error(tokens[fn_index].span(), "expected a function name")),
+    };
+    let body_index = match tokens.iter().rposition(is_brace_group) {
+        Some(index) if index > fn_index => index,
+        _ => {
+            return Err(error(
+                tokens[fn_index].span(),
+                "#[timed] requires a function with a body",
+            ))
+        }
+    };
+
+    let tag = match tag {
+        Some(tag) => TokenTree::Literal(tag).into(),
+        None => {
+            let path = match self_ty {
+                Some(self_ty) => format!("::{}::{}", self_ty, name),
+                None => format!("::{}", name),
+            };
+            call(
+                "concat",
+                [
+                    call("module_path", []),
+                    TokenTree::Literal(Literal::string(&path)).into(),
+                ],
+            )
+        }
+    };
+    let body = tokens[body_index].clone();
+    let span = body.span();
+    // Async functions are timed with `timer_async!`, awaited in place, so
+    // that the span is not held across `.await` points.
+    let mut timed = call_path(
+        &["quick_timer", "__timed"],
+        is_async
+            .then(|| Ident::new("async", span).into())
+            .into_iter()
+            .chain(tag)
+            .chain([Punct::new(',', Spacing::Alone).into(), body])
+            .collect(),
+    );
+    if is_async {
+        timed.extend([
+            TokenTree::from(Punct::new('.', Spacing::Alone)),
+            Ident::new("await", span).into(),
+        ]);
+    }
+    let mut wrapped = Group::new(Delimiter::Brace, timed);
+    wrapped.set_span(span);
+    tokens[body_index] = wrapped.into();
+    Ok(())
+}
+
+/// Instruments every method of the `impl` block whose `impl` keyword is at
+/// `impl_index`. Methods that carry their own `#[timed]` are left to it.
+fn instrument_impl(
+    tokens: &mut [TokenTree],
+    impl_index: usize,
+    tag: Option<Literal>,
+) -> Result<(), TokenStream> {
+    if let Some(tag) = tag {
+        return Err(error(
+            tag.span(),
+            "a tag can only be given to #[timed] on a single function",
+        ));
+    }
+    let body_index = match tokens.iter().rposition(is_brace_group) {
+        Some(index) if index > impl_index => index,
+        _ => return Err(error(tokens[impl_index].span(), "expected an impl body")),
+    };
+    let self_ty = self_type_name(&tokens[impl_index + 1..body_index]);
+
+    let body = match &tokens[body_index] {
+        TokenTree::Group(group) => group.clone(),
+        _ => unreachable!(),
+    };
+    let mut items: Vec<TokenTree> = body.stream().into_iter().collect();
+    let mut start = 0;
+    while start < items.len() {
+        // Each item ends after its brace-delimited body or at a `;`.
+        let end = items[start..]
+            .iter()
+            .position(|t| is_brace_group(t) || is_punct(t, ';'))
+            .map_or(items.len(), |offset| start + offset + 1);
+        let item = &mut items[start..end];
+        let already_timed = item.windows(2).any(|pair| {
+            is_punct(&pair[0], '#')
+                && matches!(&pair[1], TokenTree::Group(group) if group.stream().into_iter().next().map_or(false, |t| is_ident(&t, "timed")))
+        });
+        if let Some(fn_index) = item.iter().position(|t| is_ident(t, "fn")) {
+            if !already_timed && item.last().map_or(false, is_brace_group) {
+                instrument_fn(item, fn_index, None, self_ty.as_deref())?;
+            }
+        }
+        start = end;
+    }
+
+    let mut instrumented = Group::new(Delimiter::Brace, items.into_iter().collect());
+    instrumented.set_span(body.span());
+    tokens[body_index] = instrumented.into();
+    Ok(())
+}
+
+/// Returns the name of the type an `impl` header is for: the last identifier
+/// before any generic arguments, after `for` if the impl is of a trait.
+fn self_type_name(header: &[TokenTree]) -> Option<String> {
+    let mut depth = 0usize;
+    let mut name = None;
+    let mut after_generics = false;
+    for token in header {
+        match token {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            TokenTree::Punct(p) if p.as_char() == '>' && depth > 0 => {
+                depth -= 1;
+                if depth == 0 && name.is_some() {
+                    after_generics = true;
+                }
+            }
+            TokenTree::Ident(ident) if depth == 0 => match ident.to_string().as_str() {
+                "for" => {
+                    name = None;
+                    after_generics = false;
+                }
+                "where" => break,
+                "dyn" | "mut" => {}
+                ident if !after_generics => name = Some(ident.to_string()),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    name
+}
+
+/// Whether `literal` is a string literal, `"..."`, or a raw one, `r#"..."#`,
+/// rather than a byte string, a character or a number.
+fn is_string_literal(literal: &Literal) -> bool {
+    let literal = literal.to_string();
+    let unprefixed = literal
+        .strip_prefix('r')
+        .map_or(literal.as_str(), |raw| raw.trim_start_matches('#'));
+    unprefixed.starts_with('"')
+}
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    matches!(token, TokenTree::Ident(ident) if ident.to_string() == name)
+}
+
+fn is_punct(token: &TokenTree, ch: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == ch)
+}
+
+fn is_brace_group(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace)
+}
+
+/// Builds `::a::b!(args)`.
+fn call_path(path: &[&str], args: TokenStream) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    for segment in path {
+        tokens.extend([
+            TokenTree::from(Punct::new(':', Spacing::Joint)),
+            Punct::new(':', Spacing::Alone).into(),
+            Ident::new(segment, Span::call_site()).into(),
+        ]);
+    }
+    tokens.extend([
+        TokenTree::from(Punct::new('!', Spacing::Alone)),
+        Group::new(Delimiter::Parenthesis, args).into(),
+    ]);
+    tokens
+}
+
+/// Builds `name!(arg, arg, ...)` for a built-in macro.
+fn call<const N: usize>(name: &str, args: [TokenStream; N]) -> TokenStream {
+    let mut list = TokenStream::new();
+    for (index, arg) in args.into_iter().enumerate() {
+        if index > 0 {
+            list.extend([TokenTree::from(Punct::new(',', Spacing::Alone))]);
+        }
+        list.extend(arg);
+    }
+    [
+        TokenTree::from(Ident::new(name, Span::call_site())),
+        Punct::new('!', Spacing::Alone).into(),
+        Group::new(Delimiter::Parenthesis, list).into(),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Builds `compile_error!("message")` pointing at `span`.
+fn error(span: Span, message: &str) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+    let mut tokens: Vec<TokenTree> = call("compile_error", [TokenTree::Literal(message).into()])
+        .into_iter()
+        .collect();
+    for token in &mut tokens {
+        token.set_span(span);
+    }
+    tokens.into_iter().collect()
+}
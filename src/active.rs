@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! The registry of `timer!` blocks that are running on any thread.
+
+use crate::{clock, HumanDuration};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, Thread, ThreadId};
+use std::time::{Duration, Instant};
+
+/// A `timer!` block that has been entered but not yet left.
+#[derive(Debug, Clone)]
+pub(crate) struct ActiveSpan {
+    /// The id of the span, unique in the process.
+    pub(crate) id: u64,
+    pub(crate) tag: &'static str,
+    /// The formatted tag, for tags with format arguments.
+    pub(crate) label: Option<String>,
+    pub(crate) file: &'static str,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+    pub(crate) module: &'static str,
+    pub(crate) start: Instant,
+}
+
+/// The spans open on one thread, outermost first.
+#[derive(Debug)]
+struct ThreadSpans {
+    thread: Thread,
+    spans: Mutex<Vec<ActiveSpan>>,
+}
+
+/// Whether spans are registered. Only [`active`] and the watchdog need them,
+/// so until either is used, timers skip the registry.
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Every thread that has opened a span. Threads remove themselves by exiting.
+static THREADS: Mutex<Vec<Weak<ThreadSpans>>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static LOCAL: Arc<ThreadSpans> = {
+        let local = Arc::new(ThreadSpans {
+            thread: thread::current(),
+            spans: Mutex::new(Vec::new()),
+        });
+        let mut threads = THREADS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        threads.retain(|thread| thread.strong_count() > 0);
+        threads.push(Arc::downgrade(&local));
+        local
+    };
+}
+
+/// Returns every thread that has opened a span and is still running.
+fn threads() -> Vec<Arc<ThreadSpans>> {
+    THREADS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/// Runs `f` on the spans of the current thread, unless it is exiting.
+fn with_local<R>(f: impl FnOnce(&mut Vec<ActiveSpan>) -> R) -> Option<R> {
+    LOCAL
+        .try_with(|local| {
+            f(&mut local
+                .spans
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()))
+        })
+        .ok()
+}
+
+/// Removes the span `id` from `spans`, returning whether it was there.
+fn remove(spans: &mut Vec<ActiveSpan>, id: u64) -> bool {
+    match spans.iter().rposition(|span| span.id == id) {
+        Some(index) => {
+            spans.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Starts keeping track of the `timer!` blocks that are running, so that
+/// [`active`] lists them.
+///
+/// Tracking costs every timer a lock and a push, so it is off until this is
+/// called or a [`Watchdog`](crate::Watchdog) is started. Only the blocks
+/// entered after that are tracked, so call this at startup, early in `main`,
+/// to see every block.
+pub fn track_active() {
+    TRACKING.store(true, Ordering::Relaxed);
+}
+
+/// Whether new spans should be registered.
+pub(crate) fn is_tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+/// Registers `span` as running on the current thread.
+pub(crate) fn enter(span: ActiveSpan) {
+    with_local(|spans| spans.push(span));
+}
+
+/// Sets the formatted tag and the start of the span `id` of the current
+/// thread.
+pub(crate) fn describe(id: u64, label: Option<String>, start: Instant) {
+    with_local(|spans| {
+        if let Some(span) = spans.iter_mut().rev().find(|span| span.id == id) {
+            span.label = label;
+            span.start = start;
+        }
+    });
+}
+
+/// Removes the span `id`, looking for it on the other threads if it was not
+/// opened on the current one.
+pub(crate) fn exit(id: u64) {
+    if with_local(|spans| remove(spans, id)) == Some(true) {
+        return;
+    }
+    for local in threads() {
+        let mut spans = local
+            .spans
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if remove(&mut spans, id) {
+            return;
+        }
+    }
+}
+
+/// The async blocks that are running, with the thread that created them.
+/// Futures may be polled on any thread, so they are not kept per thread.
+static FUTURES: Mutex<Vec<(Thread, ActiveSpan)>> = Mutex::new(Vec::new());
+
+/// Keeps a `timer_async!` block in the registry until dropped.
+#[derive(Debug)]
+pub(crate) struct FutureEntry {
+    thread: ThreadId,
+    id: u64,
+}
+
+impl FutureEntry {
+    /// Registers `span` as running, created on the current thread.
+    pub(crate) fn enter(span: ActiveSpan) -> Self {
+        let thread = thread::current();
+        let entry = Self {
+            thread: thread.id(),
+            id: span.id,
+        };
+        futures().push((thread, span));
+        entry
+    }
+}
+
+impl Drop for FutureEntry {
+    fn drop(&mut self) {
+        let mut futures = futures();
+        if let Some(index) = futures
+            .iter()
+            .position(|(thread, span)| thread.id() == self.thread && span.id == self.id)
+        {
+            futures.remove(index);
+        }
+    }
+}
+
+fn futures() -> std::sync::MutexGuard<'static, Vec<(Thread, ActiveSpan)>> {
+    FUTURES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A `timer!` block that is running, as returned by [`active`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ActiveTimer {
+    /// The tag given to the timer, or `"Timer"` when none was given.
+    pub tag: &'static str,
+    /// The tag formatted with its arguments, for timers whose tag has them.
+    pub label: Option<String>,
+    /// The source file of the timer, as reported by `file!()`.
+    pub file: &'static str,
+    /// The source line of the timer, as reported by `line!()`.
+    pub line: u32,
+    /// The source column of the timer, as reported by `column!()`.
+    pub column: u32,
+    /// The module path of the timer, as reported by `module_path!()`.
+    pub module: &'static str,
+    /// The thread the block is running on, or for async blocks, the thread
+    /// that created them.
+    pub thread: Thread,
+    /// How many running `timer!` blocks enclose this one on its thread.
+    /// Always 0 for async blocks.
+    pub depth: usize,
+    /// When the block started.
+    pub start: Instant,
+    /// How long the block has been running.
+    pub elapsed: Duration,
+    /// The id of the span, unique in the process.
+    pub(crate) id: u64,
+}
+
+impl ActiveTimer {
+    fn new(span: &ActiveSpan, thread: &Thread, depth: usize, now: Instant) -> Self {
+        Self {
+            tag: span.tag,
+            label: span.label.clone(),
+            file: span.file,
+            line: span.line,
+            column: span.column,
+            module: span.module,
+            thread: thread.clone(),
+            depth,
+            start: span.start,
+            elapsed: now.saturating_duration_since(span.start),
+            id: span.id,
+        }
+    }
+
+    /// The formatted tag if there is one, and the tag otherwise.
+    pub fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or(self.tag)
+    }
+
+    /// The name of the thread, or its id if it has none.
+    pub(crate) fn thread_name(&self) -> String {
+        match self.thread.name() {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", self.thread.id()),
+        }
+    }
+}
+
+/// Formats the timer as `in FILE line N TAG: running for X (thread NAME)`.
+impl fmt::Display for ActiveTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "in {} line {} {}: running for {} (thread {})",
+            self.file,
+            self.line,
+            self.name(),
+            HumanDuration::new(self.elapsed),
+            self.thread_name()
+        )
+    }
+}
+
+/// Returns every `timer!` block that is running right now, on any thread.
+///
+/// Timers are grouped by thread, in the order the threads first ran a timer,
+/// and listed outermost first on each thread. Async blocks timed by
+/// `timer_async!` or `#[timed]` follow, oldest first. Use [`report_active`] to print
+/// them, for example from a signal handler or a debug endpoint when a program
+/// seems stuck.
+///
+/// Only the blocks entered since tracking started are listed, and nothing is
+/// listed until [`track_active`] is called at startup or a
+/// [`Watchdog`](crate::Watchdog) is started. Calling this does not start
+/// tracking by itself.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{active, timer, track_active};
+///
+/// track_active();
+/// timer!(# "load" {
+///     for timer in active() {
+///         println!("{} on {:?}: {:?}", timer.name(), timer.thread.name(), timer.elapsed);
+///     }
+/// });
+/// ```
+pub fn active() -> Vec<ActiveTimer> {
+    let mut active = Vec::new();
+    for local in threads() {
+        let spans = local
+            .spans
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = clock::now();
+        active.extend(
+            spans
+                .iter()
+                .enumerate()
+                .map(|(depth, span)| ActiveTimer::new(span, &local.thread, depth, now)),
+        );
+    }
+    let futures = futures();
+    let now = clock::now();
+    active.extend(
+        futures
+            .iter()
+            .map(|(thread, span)| ActiveTimer::new(span, thread, 0, now)),
+    );
+    active
+}
+
+/// Prints every running `timer!` block to stderr, one per line, indented by
+/// how deeply it is nested.
+pub fn report_active() {
+    let stderr = io::stderr();
+    let _ = write_active(&mut stderr.lock());
+}
+
+/// Writes the list printed by [`report_active`] to `out`.
+pub fn write_active<W: Write>(out: &mut W) -> io::Result<()> {
+    let timers = active();
+    match timers.len() {
+        0 => writeln!(out, "no active timers")?,
+        1 => writeln!(out, "1 active timer:")?,
+        count => writeln!(out, "{} active timers:", count)?,
+    }
+    for timer in &timers {
+        writeln!(out, "{:indent$}{}", "", timer, indent = 2 + timer.depth * 2)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "release_also")))]
+mod tests {
+    use super::*;
+    use crate::sink::testing::capture;
+    use crate::{timer, with_clock, MockClock};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_active_timers() {
+        track_active();
+        let (entered, wait) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let worker = thread::Builder::new()
+            .name("active worker".to_string())
+            .spawn(move || {
+                timer!(# "active worker" {
+                    entered.send(()).unwrap();
+                    released.recv().unwrap();
+                });
+            })
+            .unwrap();
+        wait.recv().unwrap();
+
+        let clock = MockClock::new();
+        let (listed, _) = capture(|| {
+            with_clock(clock.clone(), || {
+                timer!(tag: "active {}", "outer"; block: {
+                    timer!(# "active inner" {
+                        clock.advance(Duration::from_millis(3));
+                        let mut out = Vec::new();
+                        write_active(&mut out).unwrap();
+                        (active(), String::from_utf8(out).unwrap())
+                    })
+                })
+            })
+        });
+        let (running, report) = listed;
+
+        let here: Vec<_> = running
+            .iter()
+            .filter(|timer| timer.thread.id() == thread::current().id())
+            .map(|timer| (timer.name(), timer.depth, timer.elapsed))
+            .collect();
+        let elapsed = Duration::from_millis(3);
+        assert_eq!(
+            here,
+            [("active outer", 0, elapsed), ("active inner", 1, elapsed)]
+        );
+        let worker_timer = running
+            .iter()
+            .find(|timer| timer.tag == "active worker")
+            .unwrap();
+        assert_eq!(worker_timer.thread.name(), Some("active worker"));
+        assert_eq!(worker_timer.depth, 0);
+        assert!(report.contains("\n  in src/active.rs line "));
+        assert!(report.contains(" active inner: running for 3.00 ms (thread "));
+
+        release.send(()).unwrap();
+        worker.join().unwrap();
+        assert!(!active()
+            .iter()
+            .any(|timer| timer.tag.starts_with("active ")));
+    }
+
+    #[test]
+    fn test_active_futures() {
+        track_active();
+        let clock = MockClock::new();
+        let listed = with_clock(clock.clone(), || {
+            let future = crate::timer_async!(# "active future" {});
+            clock.advance(Duration::from_millis(4));
+            let listed: Vec<_> = active()
+                .into_iter()
+                .filter(|timer| timer.tag == "active future")
+                .map(|timer| (timer.thread.id(), timer.depth, timer.elapsed))
+                .collect();
+            drop(future);
+            listed
+        });
+
+        assert_eq!(
+            listed,
+            [(thread::current().id(), 0, Duration::from_millis(4))]
+        );
+        // Futures leave the registry when dropped, even if never polled.
+        assert!(!active().iter().any(|timer| timer.tag == "active future"));
+    }
+}
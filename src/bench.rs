@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Micro-benchmarking: repeated runs with warmup and summary statistics.
+
+use crate::{clock, HumanDuration};
+use std::fmt;
+use std::time::Duration;
+
+/// Hides `value` from the optimizer, so that computations feeding into it, or
+/// depending on it, are not optimized away.
+///
+/// Inputs of a benchmark should be passed through `black_box` so that the
+/// compiler cannot precompute the result; outputs are passed through it by
+/// [`Bench::run`] already.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{black_box, Bench};
+/// use std::time::Duration;
+///
+/// let stats = Bench::new()
+///     .warmup(Duration::from_millis(1))
+///     .measurement_time(Duration::from_millis(10))
+///     .run(|| black_box(20u64).pow(2));
+/// assert!(stats.min <= stats.median);
+/// ```
+#[inline(never)]
+pub fn black_box<T>(value: T) -> T {
+    // SAFETY: `value` is read once and then forgotten, so it is moved rather
+    // than duplicated. The volatile read cannot be optimized away.
+    unsafe {
+        let result = std::ptr::read_volatile(&value);
+        std::mem::forget(value);
+        result
+    }
+}
+
+/// The settings of a benchmark.
+///
+/// A benchmark first runs the code repeatedly for the warmup time, which also
+/// tells how long one run takes. It then takes a number of samples, each
+/// running the code as many times as fits in an equal share of the measurement
+/// time, so that even code taking nanoseconds is measured accurately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bench {
+    pub(crate) tag: &'static str,
+    pub(crate) warmup: Duration,
+    pub(crate) measurement_time: Duration,
+    pub(crate) samples: usize,
+}
+
+impl Bench {
+    /// Creates a benchmark with 100 ms of warmup and 50 samples taken over
+    /// 500 ms.
+    pub fn new() -> Self {
+        Self {
+            tag: "Bench",
+            warmup: Duration::from_millis(100),
+            measurement_time: Duration::from_millis(500),
+            samples: 50,
+        }
+    }
+
+    /// Sets the tag of the results, which is `"Bench"` by default.
+    pub fn tag(mut self, tag: &'static str) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Sets how long the code is run before measuring.
+    pub fn warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Sets how long the measurement should take in total.
+    pub fn measurement_time(mut self, measurement_time: Duration) -> Self {
+        self.measurement_time = measurement_time;
+        self
+    }
+
+    /// Sets how many samples are taken, at least 2.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(2);
+        self
+    }
+
+    /// Benchmarks `f`, passing its result through [`black_box`].
+    ///
+    /// The time is read through the installed [clock](crate::Clock), which
+    /// must advance for the warmup to end.
+    pub fn run<T, F: FnMut() -> T>(&self, mut f: F) -> BenchStats {
+        let iterations = self.calibrate(&mut f);
+        let samples = (0..self.samples)
+            .map(|_| sample(&mut f, iterations))
+            .collect();
+        BenchStats::new(self.tag, iterations, samples)
+    }
+
+    /// Runs `f` for the warmup time, and returns how many runs of it fit in
+    /// one sample.
+    pub(crate) fn calibrate<T>(&self, f: &mut impl FnMut() -> T) -> u64 {
+        let start = clock::now();
+        let mut batch = 1u64;
+        let mut runs = 0u64;
+        let elapsed = loop {
+            for _ in 0..batch {
+                black_box(f());
+            }
+            runs += batch;
+            let elapsed = clock::elapsed(start);
+            if elapsed >= self.warmup {
+                break elapsed;
+            }
+            batch = batch.saturating_mul(2);
+        };
+        let per_run = elapsed.as_nanos() as f64 / runs as f64;
+        let per_sample = self.measurement_time.as_nanos() as f64 / self.samples as f64;
+        if per_run > 0.0 {
+            ((per_sample / per_run).ceil() as u64).max(1)
+        } else {
+            1
+        }
+    }
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `f` `iterations` times, and returns the mean time of one run in
+/// nanoseconds.
+pub(crate) fn sample<T>(f: &mut impl FnMut() -> T, iterations: u64) -> f64 {
+    let start = clock::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    clock::elapsed(start).as_nanos() as f64 / iterations as f64
+}
+
+/// Benchmarks `f` with the default [`Bench`] settings.
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{bench, black_box};
+///
+/// let stats = bench(|| black_box(1u64) + 1);
+/// println!("{}", stats);
+/// ```
+pub fn bench<T, F: FnMut() -> T>(f: F) -> BenchStats {
+    Bench::new().run(f)
+}
+
+/// How many samples fell outside the Tukey fences of a benchmark.
+///
+/// Samples more than 1.5 interquartile ranges beyond the first or third
+/// quartile are mild outliers; beyond 3 interquartile ranges, severe ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Outliers {
+    /// Severe outliers below the first quartile.
+    pub low_severe: usize,
+    /// Mild outliers below the first quartile.
+    pub low_mild: usize,
+    /// Mild outliers above the third quartile.
+    pub high_mild: usize,
+    /// Severe outliers above the third quartile.
+    pub high_severe: usize,
+}
+
+impl Outliers {
+    /// The number of outliers of any kind.
+    pub fn total(&self) -> usize {
+        self.low_severe + self.low_mild + self.high_mild + self.high_severe
+    }
+}
+
+/// The results of a benchmark: statistics over the time of one run, across
+/// samples.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BenchStats {
+    /// The tag given to the benchmark.
+    pub tag: &'static str,
+    /// How many times the code was run per sample.
+    pub iterations: u64,
+    /// The mean time of one run in each sample, in nanoseconds.
+    pub samples: Vec<f64>,
+    /// The mean across samples.
+    pub mean: Duration,
+    /// The median across samples.
+    pub median: Duration,
+    /// The sample standard deviation.
+    pub std_dev: Duration,
+    /// The fastest sample.
+    pub min: Duration,
+    /// The slowest sample.
+    pub max: Duration,
+    /// The median absolute deviation from the median.
+    pub mad: Duration,
+    /// The samples that are outliers.
+    pub outliers: Outliers,
+}
+
+impl BenchStats {
+    pub(crate) fn new(tag: &'static str, iterations: u64, samples: Vec<f64>) -> Self {
+        let mut sorted = samples.clone();
+        sort(&mut sorted);
+        let median = percentile(&sorted, 50.0);
+        let mut deviations: Vec<f64> = sorted.iter().map(|x| (x - median).abs()).collect();
+        sort(&mut deviations);
+
+        Self {
+            tag,
+            iterations,
+            mean: nanos(mean(&sorted)),
+            median: nanos(median),
+            std_dev: nanos(std_dev(&sorted)),
+            min: nanos(sorted.first().copied().unwrap_or(0.0)),
+            max: nanos(sorted.last().copied().unwrap_or(0.0)),
+            mad: nanos(percentile(&deviations, 50.0)),
+            outliers: outliers(&sorted),
+            samples,
+        }
+    }
+}
+
+/// Formats the results as `TAG: MEAN ± STD_DEV (median …, min …, max …,
+/// MAD …; N samples of M runs, K outliers)`.
+impl fmt::Display for BenchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} ± {} (median {}, min {}, max {}, MAD {}; {} samples of {} runs",
+            self.tag,
+            HumanDuration::new(self.mean),
+            HumanDuration::new(self.std_dev),
+            HumanDuration::new(self.median),
+            HumanDuration::new(self.min),
+            HumanDuration::new(self.max),
+            HumanDuration::new(self.mad),
+            self.samples.len(),
+            self.iterations,
+        )?;
+        match self.outliers.total() {
+            0 => write!(f, ")"),
+            1 => write!(f, ", 1 outlier)"),
+            n => write!(f, ", {} outliers)", n),
+        }
+    }
+}
+
+fn nanos(value: f64) -> Duration {
+    Duration::from_nanos(value.max(0.0).round() as u64)
+}
+
+pub(crate) fn sort(values: &mut [f64]) {
+    values.sort_by(|a, b| a.total_cmp(b));
+}
+
+pub(crate) fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// The sample variance, with Bessel's correction.
+pub(crate) fn variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+pub(crate) fn std_dev(values: &[f64]) -> f64 {
+    variance(values).sqrt()
+}
+
+/// The `p`th percentile of sorted `values`, interpolating between the two
+/// nearest values.
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        len => {
+            let rank = p.clamp(0.0, 100.0) / 100.0 * (len - 1) as f64;
+            let below = rank.floor() as usize;
+            let above = rank.ceil() as usize;
+            sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+        }
+    }
+}
+
+fn outliers(sorted: &[f64]) -> Outliers {
+    let q1 = percentile(sorted, 25.0);
+    let q3 = percentile(sorted, 75.0);
+    let iqr = q3 - q1;
+    let mut outliers = Outliers::default();
+    for &x in sorted {
+        if x < q1 - 3.0 * iqr {
+            outliers.low_severe += 1;
+        } else if x < q1 - 1.5 * iqr {
+            outliers.low_mild += 1;
+        } else if x > q3 + 3.0 * iqr {
+            outliers.high_severe += 1;
+        } else if x > q3 + 1.5 * iqr {
+            outliers.high_mild += 1;
+        }
+    }
+    outliers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{with_clock, MockClock};
+
+    #[test]
+    fn test_stats() {
+        let samples = vec![10.0, 12.0, 11.0, 13.0, 11.0, 12.0, 40.0, 12.0, 11.0, 0.0];
+        let stats = BenchStats::new("stats", 1, samples);
+        assert_eq!(stats.min, Duration::ZERO);
+        assert_eq!(stats.max, Duration::from_nanos(40));
+        assert_eq!(stats.median, Duration::from_nanos(12)); // 11.5, rounded
+        assert_eq!(stats.mean, Duration::from_nanos(13)); // 13.2
+        assert_eq!(stats.mad, Duration::from_nanos(1));
+        assert_eq!(stats.std_dev, Duration::from_nanos(10)); // 10.36
+        assert_eq!(
+            stats.outliers,
+            Outliers {
+                low_severe: 1,
+                high_severe: 1,
+                ..Outliers::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_calibration() {
+        let clock = MockClock::new();
+        let bench = Bench::new()
+            .warmup(Duration::from_micros(100))
+            .measurement_time(Duration::from_micros(1000))
+            .samples(10);
+        let stats = with_clock(clock.clone(), || {
+            bench.run(|| clock.advance(Duration::from_nanos(250)))
+        });
+
+        // 1000 µs over 10 samples of 250 ns runs.
+        assert_eq!(stats.iterations, 400);
+        assert_eq!(stats.samples, vec![250.0; 10]);
+        assert_eq!(stats.mean, Duration::from_nanos(250));
+        assert_eq!(stats.outliers.total(), 0);
+    }
+
+    #[test]
+    fn test_black_box_is_identity() {
+        assert_eq!(black_box(vec![1, 2, 3]), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Asserting that code runs within a duration budget.
+
+use crate::{clock, HumanDuration};
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/// The error returned by `try_timer_assert!` when a block took longer than its
+/// budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BudgetExceeded {
+    /// The tag given to the assertion, or `"Budget"` when none was given.
+    pub tag: &'static str,
+    /// The source file of the assertion, as reported by `file!()`.
+    pub file: &'static str,
+    /// The source line of the assertion, as reported by `line!()`.
+    pub line: u32,
+    /// The time the block took, in its fastest run.
+    pub elapsed: Duration,
+    /// The time the block was allowed to take.
+    pub budget: Duration,
+    /// How many times the block was run.
+    pub runs: usize,
+}
+
+/// Formats the error as `in FILE line N TAG: X exceeds the budget of Y`,
+/// followed by `(best of N runs)` if the block was run more than once.
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "in {} line {} {}: {} exceeds the budget of {}",
+            self.file,
+            self.line,
+            self.tag,
+            HumanDuration::new(self.elapsed),
+            HumanDuration::new(self.budget)
+        )?;
+        if self.runs > 1 {
+            write!(f, " (best of {} runs)", self.runs)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+/// Runs `f` `runs` times, at least once, and checks that its fastest run took
+/// no longer than `budget`. Returns the result of the last run.
+#[doc(hidden)]
+pub fn check_budget<T>(
+    tag: &'static str,
+    file: &'static str,
+    line: u32,
+    budget: Duration,
+    runs: usize,
+    mut f: impl FnMut() -> T,
+) -> Result<T, BudgetExceeded> {
+    let runs = runs.max(1);
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..runs {
+        let start = clock::now();
+        let value = f();
+        best = best.min(clock::elapsed(start));
+        result = Some(value);
+    }
+    if best > budget {
+        return Err(BudgetExceeded {
+            tag,
+            file,
+            line,
+            elapsed: best,
+            budget,
+            runs,
+        });
+    }
+    Ok(result.expect("the block runs at least once"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{timer_assert, try_timer_assert, with_clock, MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn test_budget() {
+        let clock = MockClock::new();
+        let advance = |millis| clock.advance(Duration::from_millis(millis));
+        with_clock(clock.clone(), || {
+            let result = try_timer_assert!(budget: 10ms, {
+                advance(10);
+                1 + 1
+            });
+            assert_eq!(result, Ok(2));
+
+            let error = try_timer_assert!(tag: "parse", budget: Duration::from_millis(10), {
+                advance(11);
+            })
+            .unwrap_err();
+            assert_eq!(error.tag, "parse");
+            assert_eq!(error.elapsed, Duration::from_millis(11));
+            assert_eq!(error.budget, Duration::from_millis(10));
+            assert!(error
+                .to_string()
+                .ends_with(" parse: 11.0 ms exceeds the budget of 10.0 ms"));
+
+            // Only the fastest run has to be within the budget.
+            let mut times = vec![30, 5, 20].into_iter();
+            let result = try_timer_assert!(budget: 10ms, runs: 3, {
+                advance(times.next().unwrap());
+            });
+            assert_eq!(result, Ok(()));
+            let error = try_timer_assert!(tag: slow, budget: 10ms, runs: 2, {
+                advance(15);
+            })
+            .unwrap_err();
+            assert_eq!((error.tag, error.runs), ("slow", 2));
+            assert!(error.to_string().ends_with(" (best of 2 runs)"));
+
+            assert_eq!(timer_assert!(budget: 1s, { "fast" }), "fast");
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "too slow: 2.00 ms exceeds the budget of 1.00 ms")]
+    fn test_assert_faster_than_panics() {
+        let clock = MockClock::new();
+        with_clock(clock.clone(), || {
+            crate::assert_faster_than!(1ms, tag: "too slow", {
+                clock.advance(Duration::from_millis(2));
+            });
+        });
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Static descriptions of the places timers are written, caching whether the
+//! filter enables them.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// The filter has not been consulted for this call site yet.
+const UNKNOWN: u8 = 0;
+const NEVER: u8 = 1;
+const ALWAYS: u8 = 2;
+
+/// Where a timer is written, declared as a `static` by each expansion of the
+/// timer macros.
+///
+/// The first time a call site runs, it asks the [filter](crate::set_filter)
+/// whether it is enabled and caches the answer, so that afterwards a disabled
+/// timer costs a single relaxed atomic load. Call sites that have asked are
+/// registered, and their answers are recomputed whenever the filter changes.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Callsite {
+    pub(crate) tag: &'static str,
+    pub(crate) file: &'static str,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+    pub(crate) module: &'static str,
+    interest: AtomicU8,
+}
+
+/// Every call site whose interest has been computed.
+static REGISTRY: Mutex<Vec<&'static Callsite>> = Mutex::new(Vec::new());
+
+impl Callsite {
+    pub const fn new(
+        tag: &'static str,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        module: &'static str,
+    ) -> Self {
+        Self {
+            tag,
+            file,
+            line,
+            column,
+            module,
+            interest: AtomicU8::new(UNKNOWN),
+        }
+    }
+
+    /// Returns whether the filter enables timers at this call site.
+    #[inline]
+    pub fn is_enabled(&'static self) -> bool {
+        match self.interest.load(Ordering::Relaxed) {
+            ALWAYS => true,
+            NEVER => false,
+            _ => self.register(),
+        }
+    }
+
+    #[cold]
+    fn register(&'static self) -> bool {
+        let mut registry = REGISTRY
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Another thread may have registered this call site meanwhile.
+        if self.interest.load(Ordering::Relaxed) == UNKNOWN {
+            registry.push(self);
+        }
+        self.store_interest()
+    }
+
+    fn store_interest(&self) -> bool {
+        let enabled = crate::filter::enabled(self.tag, self.module);
+        let interest = if enabled { ALWAYS } else { NEVER };
+        self.interest.store(interest, Ordering::Relaxed);
+        enabled
+    }
+}
+
+/// Recomputes the interest of every registered call site, after the filter
+/// has changed.
+pub(crate) fn rebuild_interest() {
+    let registry = REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for callsite in registry.iter() {
+        callsite.store_interest();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interest_follows_the_filter() {
+        use crate::filter::testing::with_filter;
+        static CALLSITE: Callsite = Callsite::new("callsite test", file!(), 1, 1, "");
+
+        with_filter("callsite test=off", || {
+            assert!(!CALLSITE.is_enabled());
+            assert_eq!(CALLSITE.interest.load(Ordering::Relaxed), NEVER);
+        });
+        assert_eq!(CALLSITE.interest.load(Ordering::Relaxed), ALWAYS);
+        assert!(CALLSITE.is_enabled());
+    }
+}
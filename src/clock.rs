@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! The source of time used by every timer.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+///
+/// All timers in this crate read the time through the installed clock,
+/// which is [`InstantClock`] unless [`set_clock`] or [`with_clock`] says
+/// otherwise. Tests can install a [`MockClock`] to get exact durations.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+/// The default clock, which reads [`Instant::now`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InstantClock;
+
+impl Clock for InstantClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so one clone can be installed while another
+/// is advanced by the test.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{timer_silent, with_clock, MockClock};
+/// use std::time::Duration;
+///
+/// let clock = MockClock::new();
+/// let (_, duration) = with_clock(clock.clone(), || {
+///     timer_silent! {
+///         clock.advance(Duration::from_millis(250));
+///     }
+/// });
+/// assert_eq!(duration, Duration::from_millis(250));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    base: Instant,
+    offset_nanos: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    /// Creates a mock clock, stopped at the current time.
+    pub fn new() -> Self {
+        Self {
+            base: Instant::now(),
+            offset_nanos: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.offset_nanos.fetch_add(nanos, Ordering::SeqCst);
+    }
+
+    /// How far the clock has been moved since it was created.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.offset_nanos.load(Ordering::SeqCst))
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.base + self.elapsed()
+    }
+}
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+/// Set once a global clock is installed, so that reading the time stays a
+/// plain `Instant::now()` until then.
+static CLOCK_SET: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static THREAD_CLOCK: RefCell<Option<Arc<dyn Clock>>> = const { RefCell::new(None) };
+}
+
+/// Installs `clock` as the global source of time, replacing the previously
+/// installed one.
+pub fn set_clock<C: Clock + 'static>(clock: C) {
+    *CLOCK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(clock));
+    CLOCK_SET.store(true, Ordering::Release);
+}
+
+/// Runs `f` with `clock` as the source of time on the current thread only.
+///
+/// This overrides the global clock, and is meant for tests that run in
+/// parallel with other tests.
+pub fn with_clock<C: Clock + 'static, R>(clock: C, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<dyn Clock>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            let _ = THREAD_CLOCK.try_with(|local| *local.borrow_mut() = previous);
+        }
+    }
+
+    let clock: Arc<dyn Clock> = Arc::new(clock);
+    let _restore = Restore(THREAD_CLOCK.with(|local| local.borrow_mut().replace(clock)));
+    f()
+}
+
+/// Returns the current time of the installed clock.
+pub fn now() -> Instant {
+    let now = read();
+    crate::record::fix_epoch(now);
+    now
+}
+
+fn read() -> Instant {
+    let local = THREAD_CLOCK
+        .try_with(|local| local.borrow().as_ref().map(|clock| clock.now()))
+        .ok()
+        .flatten();
+    if let Some(now) = local {
+        return now;
+    }
+    if CLOCK_SET.load(Ordering::Acquire) {
+        let clock = CLOCK
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(clock) = clock {
+            return clock.now();
+        }
+    }
+    Instant::now()
+}
+
+/// Returns the time since `start` on the installed clock.
+pub(crate) fn elapsed(start: Instant) -> Duration {
+    now().saturating_duration_since(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_clock_is_scoped_to_the_thread() {
+        let clock = MockClock::new();
+        let start = with_clock(clock.clone(), now);
+        with_clock(clock.clone(), || {
+            clock.advance(Duration::from_secs(3));
+            assert_eq!(elapsed(start), Duration::from_secs(3));
+            std::thread::spawn(move || assert!(elapsed(start) < Duration::from_secs(3)))
+                .join()
+                .unwrap();
+        });
+        assert_eq!(clock.elapsed(), Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Comparing the speed of several variants of the same code.
+
+use crate::bench::{self, sample, Bench, BenchStats};
+use std::fmt;
+
+/// How many times the samples are resampled to estimate confidence intervals.
+const RESAMPLES: usize = 1000;
+/// The significance level of the verdicts.
+const ALPHA: f64 = 0.05;
+
+/// A tagged variant of the code, as passed to [`Bench::compare`].
+pub type Variant<'a> = (&'static str, Box<dyn FnMut() + 'a>);
+
+/// Whether a variant is faster than the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// Significantly faster than the baseline.
+    Faster,
+    /// Significantly slower than the baseline.
+    Slower,
+    /// Not significantly different from the baseline.
+    NoDifference,
+}
+
+/// How one variant compares with the baseline, the first variant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct Relative {
+    /// The tag of the variant.
+    pub tag: &'static str,
+    /// The tag of the baseline.
+    pub baseline: &'static str,
+    /// How many times faster the variant is: the mean time of the baseline
+    /// divided by that of the variant. Below 1 if the variant is slower.
+    pub speedup: f64,
+    /// A 95% bootstrap confidence interval for `speedup`.
+    pub confidence_interval: (f64, f64),
+    /// The two-sided p-value of a Mann-Whitney U test of the samples.
+    pub p_value: f64,
+    /// Faster or slower if `p_value` is below 0.05 and the confidence
+    /// interval excludes 1.
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Relative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (low, high) = self.confidence_interval;
+        match self.verdict {
+            Verdict::Faster => write!(
+                f,
+                "{} is {:.2}x faster than {} [{:.2}x, {:.2}x], p = {:.3}",
+                self.tag, self.speedup, self.baseline, low, high, self.p_value
+            ),
+            Verdict::Slower => write!(
+                f,
+                "{} is {:.2}x slower than {} [{:.2}x, {:.2}x], p = {:.3}",
+                self.tag,
+                1.0 / self.speedup,
+                self.baseline,
+                1.0 / high,
+                1.0 / low,
+                self.p_value
+            ),
+            Verdict::NoDifference => write!(
+                f,
+                "{} is not significantly different from {} ({:.2}x [{:.2}x, {:.2}x]), p = {:.3}",
+                self.tag, self.baseline, self.speedup, low, high, self.p_value
+            ),
+        }
+    }
+}
+
+/// The results of [`Bench::compare`] and `compare!`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Comparison {
+    /// The statistics of each variant, in the order they were given.
+    pub results: Vec<BenchStats>,
+    /// How each variant after the first compares with the first.
+    pub relative: Vec<Relative>,
+}
+
+/// Prints the statistics of every variant, then how each compares with the
+/// baseline, one per line.
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stats in &self.results {
+            writeln!(f, "{}", stats)?;
+        }
+        for (index, relative) in self.relative.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", relative)?;
+        }
+        Ok(())
+    }
+}
+
+impl Bench {
+    /// Benchmarks several variants of the same code against each other.
+    ///
+    /// Each variant is warmed up and calibrated on its own, then their samples
+    /// are taken in turns, so that drift in the speed of the machine affects
+    /// every variant alike. Every variant after the first is compared with the
+    /// first, the baseline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than two variants are given.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use quick_timer::{black_box, Bench};
+    ///
+    /// let data: Vec<u32> = (0..1000).collect();
+    /// let comparison = Bench::new().compare(vec![
+    ///     ("linear", Box::new(|| { black_box(data.iter().position(|&x| x == black_box(700))); })),
+    ///     ("binary", Box::new(|| { black_box(data.binary_search(&black_box(700))); })),
+    /// ]);
+    /// println!("{}", comparison);
+    /// ```
+    pub fn compare(&self, mut variants: Vec<Variant<'_>>) -> Comparison {
+        assert!(
+            variants.len() >= 2,
+            "at least two variants are needed for a comparison"
+        );
+        let iterations: Vec<u64> = variants
+            .iter_mut()
+            .map(|(_, f)| self.calibrate(f))
+            .collect();
+
+        let count = variants.len();
+        let mut samples = vec![Vec::with_capacity(self.samples); count];
+        for round in 0..self.samples {
+            // Rotate which variant goes first, so none always follows another.
+            for offset in 0..count {
+                let index = (round + offset) % count;
+                let time = sample(&mut variants[index].1, iterations[index]);
+                samples[index].push(time);
+            }
+        }
+
+        let results: Vec<BenchStats> = variants
+            .iter()
+            .zip(iterations)
+            .zip(samples)
+            .map(|(((tag, _), iterations), samples)| BenchStats::new(tag, iterations, samples))
+            .collect();
+        let baseline = &results[0];
+        let relative = results[1..]
+            .iter()
+            .map(|variant| relative(baseline, variant))
+            .collect();
+        Comparison { results, relative }
+    }
+}
+
+/// Compares `variant` with `baseline`.
+fn relative(baseline: &BenchStats, variant: &BenchStats) -> Relative {
+    let speedup = ratio(&baseline.samples, &variant.samples);
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let mut ratios: Vec<f64> = (0..RESAMPLES)
+        .map(|_| {
+            ratio(
+                &rng.resample(&baseline.samples),
+                &rng.resample(&variant.samples),
+            )
+        })
+        .collect();
+    bench::sort(&mut ratios);
+    let confidence_interval = (
+        bench::percentile(&ratios, 100.0 * ALPHA / 2.0),
+        bench::percentile(&ratios, 100.0 * (1.0 - ALPHA / 2.0)),
+    );
+    let p_value = mann_whitney_u(&baseline.samples, &variant.samples);
+
+    let verdict = if p_value >= ALPHA {
+        Verdict::NoDifference
+    } else if confidence_interval.0 > 1.0 {
+        Verdict::Faster
+    } else if confidence_interval.1 < 1.0 {
+        Verdict::Slower
+    } else {
+        Verdict::NoDifference
+    };
+    Relative {
+        tag: variant.tag,
+        baseline: baseline.tag,
+        speedup,
+        confidence_interval,
+        p_value,
+        verdict,
+    }
+}
+
+/// The ratio of the means of `baseline` and `variant`.
+fn ratio(baseline: &[f64], variant: &[f64]) -> f64 {
+    let variant = bench::mean(variant);
+    if variant > 0.0 {
+        bench::mean(baseline) / variant
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// The two-sided p-value of the Mann-Whitney U test that `a` and `b` come
+/// from the same distribution, using the normal approximation with a
+/// correction for ties.
+fn mann_whitney_u(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&x| (x, true))
+        .chain(b.iter().map(|&x| (x, false)))
+        .collect();
+    all.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Tied values share the mean of their ranks.
+    let mut rank_sum_a = 0.0;
+    let mut ties = 0.0;
+    let mut start = 0;
+    while start < all.len() {
+        let end = start
+            + all[start..]
+                .iter()
+                .take_while(|x| x.0 == all[start].0)
+                .count();
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum_a += rank * all[start..end].iter().filter(|x| x.1).count() as f64;
+        let t = (end - start) as f64;
+        ties += t * t * t - t;
+        start = end;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    // With a continuity correction towards the mean.
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// The complementary error function, with a fractional error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+/// A small xorshift* generator, so that resampling is reproducible.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Draws `values.len()` values from `values`, with replacement.
+    fn resample(&mut self, values: &[f64]) -> Vec<f64> {
+        (0..values.len())
+            .map(|_| values[(self.next() % values.len() as u64) as usize])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{with_clock, MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let b = [9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0];
+        assert!(mann_whitney_u(&a, &b) < 0.001);
+        let c = [1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5, 8.5];
+        assert!(mann_whitney_u(&a, &c) > 0.5);
+        assert_eq!(mann_whitney_u(&[1.0; 5], &[1.0; 5]), 1.0);
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((erfc(1.0) - 0.157_299_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compare() {
+        let clock = MockClock::new();
+        let bench = Bench::new()
+            .warmup(Duration::from_micros(10))
+            .measurement_time(Duration::from_micros(100))
+            .samples(10);
+        let advance = |nanos| {
+            let clock = clock.clone();
+            Box::new(move || clock.advance(Duration::from_nanos(nanos))) as Box<dyn FnMut()>
+        };
+        let comparison = with_clock(clock.clone(), || {
+            bench.compare(vec![
+                ("slow", advance(200)),
+                ("fast", advance(100)),
+                ("same", advance(200)),
+                ("slower", advance(400)),
+            ])
+        });
+
+        let verdicts: Vec<_> = comparison
+            .relative
+            .iter()
+            .map(|r| (r.tag, r.speedup, r.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("fast", 2.0, Verdict::Faster),
+                ("same", 1.0, Verdict::NoDifference),
+                ("slower", 0.5, Verdict::Slower),
+            ]
+        );
+        assert_eq!(comparison.relative[0].confidence_interval, (2.0, 2.0));
+        assert_eq!(
+            comparison.relative[2].to_string(),
+            "slower is 2.00x slower than slow [2.00x, 2.00x], p = 0.000"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Typed key-value fields attached to timings.
+
+use std::fmt;
+
+/// The value of a field given to `timer!` with `fields: key = value`.
+///
+/// Fields keep their type, so that structured sinks such as
+/// [`JsonSink`](crate::JsonSink) can write numbers as numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    I64(i64),
+    /// An unsigned integer.
+    U64(u64),
+    /// A floating-point number.
+    F64(f64),
+    /// A string.
+    Str(String),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Bool(value) => value.fmt(f),
+            FieldValue::I64(value) => value.fmt(f),
+            FieldValue::U64(value) => value.fmt(f),
+            FieldValue::F64(value) => value.fmt(f),
+            FieldValue::Str(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Values that can be given as fields to `timer!`.
+///
+/// `timer!` only borrows the value of a field, and converts it when the timer
+/// is enabled. Integers, floats, booleans, characters and strings, and
+/// references to them, are fields already; implement this trait to use other
+/// types as fields.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{FieldValue, ToFieldValue};
+///
+/// struct UserId(u32);
+///
+/// impl ToFieldValue for UserId {
+///     fn to_field_value(&self) -> FieldValue {
+///         FieldValue::U64(self.0.into())
+///     }
+/// }
+///
+/// assert_eq!(UserId(7).to_field_value(), FieldValue::U64(7));
+/// assert_eq!("seven".to_field_value(), FieldValue::Str("seven".to_string()));
+/// ```
+pub trait ToFieldValue {
+    /// Converts the value into a field.
+    fn to_field_value(&self) -> FieldValue;
+}
+
+impl<T: ToFieldValue + ?Sized> ToFieldValue for &T {
+    fn to_field_value(&self) -> FieldValue {
+        (**self).to_field_value()
+    }
+}
+
+impl ToFieldValue for FieldValue {
+    fn to_field_value(&self) -> FieldValue {
+        self.clone()
+    }
+}
+
+macro_rules! impl_to_field_value {
+    ($variant:ident($target:ty): $($source:ty),*) => {
+        $(
+            impl ToFieldValue for $source {
+                fn to_field_value(&self) -> FieldValue {
+                    FieldValue::$variant(*self as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_field_value!(I64(i64): i8, i16, i32, i64, isize);
+impl_to_field_value!(U64(u64): u8, u16, u32, u64, usize);
+impl_to_field_value!(F64(f64): f32, f64);
+
+impl ToFieldValue for bool {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Bool(*self)
+    }
+}
+
+impl ToFieldValue for char {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Str(self.to_string())
+    }
+}
+
+impl ToFieldValue for str {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Str(self.to_string())
+    }
+}
+
+impl ToFieldValue for String {
+    fn to_field_value(&self) -> FieldValue {
+        FieldValue::Str(self.clone())
+    }
+}
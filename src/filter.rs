@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Runtime enabling and disabling of timers, configured like `env_logger`.
+
+use std::sync::RwLock;
+
+/// The environment variable the filter is read from.
+const FILTER_ENV: &str = "QUICK_TIMER";
+
+/// One `pattern=on|off` entry of a filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    /// `None` for a bare `on` or `off`, which applies to every timer.
+    pattern: Option<String>,
+    enabled: bool,
+}
+
+impl Directive {
+    fn matches(&self, tag: &str, module: &str) -> bool {
+        match &self.pattern {
+            None => true,
+            Some(pattern) => {
+                glob_match(pattern, tag)
+                    || module
+                        .strip_prefix(pattern.as_str())
+                        .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+            }
+        }
+    }
+
+    /// More specific directives take precedence over less specific ones.
+    fn specificity(&self) -> usize {
+        self.pattern.as_ref().map_or(0, |pattern| pattern.len() + 1)
+    }
+}
+
+/// A parsed filter, deciding which timers are enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Filter {
+    directives: Vec<Directive>,
+}
+
+impl Filter {
+    fn parse(spec: &str) -> Self {
+        let mut directives: Vec<Directive> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+            .filter_map(|directive| {
+                let (pattern, value) = match directive.split_once('=') {
+                    Some((pattern, value)) => (Some(pattern.trim()), value.trim()),
+                    None => match parse_switch(directive) {
+                        Some(_) => (None, directive),
+                        None => (Some(directive), "on"),
+                    },
+                };
+                match parse_switch(value) {
+                    Some(enabled) => Some(Directive {
+                        pattern: pattern.map(str::to_string),
+                        enabled,
+                    }),
+                    None => {
+                        eprintln!(
+                            "quick-timer: ignoring invalid {} directive `{}`",
+                            FILTER_ENV, directive
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        // Most specific first, so the first match decides.
+        directives.sort_by_key(|directive| std::cmp::Reverse(directive.specificity()));
+        Self { directives }
+    }
+
+    fn enabled(&self, tag: &str, module: &str) -> bool {
+        self.directives
+            .iter()
+            .find(|directive| directive.matches(tag, module))
+            .map_or(true, |directive| directive.enabled)
+    }
+}
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" | "1" => Some(true),
+        "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match text.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts = parts.peekable();
+    if parts.peek().is_none() {
+        return rest.is_empty();
+    }
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+static FILTER: RwLock<Option<Filter>> = RwLock::new(None);
+
+/// Replaces the filter deciding which timers are enabled.
+///
+/// The filter is a comma-separated list of `pattern=on` or `pattern=off`
+/// directives, plus an optional bare `on` or `off` for every other timer. A
+/// pattern matches a timer if it matches its tag, where `*` matches any run of
+/// characters, or if it is a prefix of its module path. When several directives
+/// match, the longest pattern wins. Timers matching no directive are enabled.
+///
+/// Until this is called, the filter is read from the `QUICK_TIMER` environment
+/// variable the first time a timer runs.
+///
+/// This decides at runtime whether timers that were compiled in run; in
+/// release builds, `timer!` is only compiled in with the `release_also` feature.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{enabled, set_filter};
+///
+/// // Only the `db` timers, and everything in `my_app::render` except its
+/// // `text` module.
+/// set_filter("off, db*=on, my_app::render=on, my_app::render::text=off");
+///
+/// assert!(enabled("db query", "my_app::db"));
+/// assert!(enabled("layout", "my_app::render::tree"));
+/// assert!(!enabled("shape", "my_app::render::text"));
+/// assert!(!enabled("parse", "my_app::input"));
+/// ```
+pub fn set_filter(spec: &str) {
+    *FILTER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Filter::parse(spec));
+    // The filter lock is released first: call sites take it while registering.
+    crate::callsite::rebuild_interest();
+}
+
+/// Returns whether the filter enables the timer tagged `tag` in `module`.
+pub fn enabled(tag: &str, module: &str) -> bool {
+    {
+        let filter = FILTER
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(filter) = &*filter {
+            return filter.enabled(tag, module);
+        }
+    }
+    let mut filter = FILTER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    filter
+        .get_or_insert_with(|| Filter::parse(&std::env::var(FILTER_ENV).unwrap_or_default()))
+        .enabled(tag, module)
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::sync::Mutex;
+
+    static INSTALLED: Mutex<()> = Mutex::new(());
+
+    /// Runs `f` with the filter set to `spec`, then enables every timer again.
+    pub(crate) fn with_filter<R>(spec: &str, f: impl FnOnce() -> R) -> R {
+        let _installed = INSTALLED
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        super::set_filter(spec);
+        let result = f();
+        super::set_filter("");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("db", "db"));
+        assert!(!glob_match("db", "db query"));
+        assert!(glob_match("db*", "db query"));
+        assert!(glob_match("*query", "db query"));
+        assert!(glob_match("d*q*y", "db query"));
+        assert!(!glob_match("d*x*y", "db query"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_filter_precedence() {
+        let filter = Filter::parse("db*=on,render=off,off");
+        assert!(filter.enabled("db query", "app"));
+        assert!(!filter.enabled("render", "app"));
+        assert!(!filter.enabled("parse", "app"));
+
+        let filter = Filter::parse("app::net=off, app::net::http");
+        assert!(!filter.enabled("send", "app::net"));
+        assert!(filter.enabled("send", "app::network"));
+        assert!(filter.enabled("get", "app::net::http::client"));
+        assert!(filter.enabled("get", "app::other"));
+
+        assert_eq!(Filter::parse(""), Filter::default());
+        assert_eq!(Filter::parse("db=maybe"), Filter::default());
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    fn test_disabled_timers_report_nothing() {
+        let (_, records) = testing::with_filter("filtered out=off", || {
+            crate::sink::testing::capture(|| {
+                crate::timer!(# "kept" {
+                    crate::timer!(# "filtered out" {});
+                });
+            })
+        });
+        let tags: Vec<_> = records.iter().map(|r| (r.tag, r.depth)).collect();
+        assert_eq!(tags, [("kept", 0)]);
+    }
+}
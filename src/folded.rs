@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A sink summing timings into folded stacks, for flamegraphs.
+
+use crate::sink::OutputFile;
+use crate::{Sink, TimingRecord};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+/// Sums the self time of every nested timer into Brendan Gregg's folded-stack
+/// format, which `flamegraph.pl` and `inferno-flamegraph` turn into
+/// flamegraphs.
+///
+/// Each line is a stack of `timer!` tags, outermost first and separated by
+/// `;`, followed by the total self time spent in it, in nanoseconds:
+///
+/// ```text
+/// frame;parse 1200000
+/// frame;render 3400000
+/// frame;render;text 800000
+/// ```
+///
+/// Tags with format arguments appear unformatted, so that the timings of one
+/// timer add up to a single frame. The stacks rely on the records of a thread
+/// arriving parents first, so timers nested in a block that held back more
+/// records than its thread buffers may be attributed to the wrong stack.
+///
+/// The stacks are kept in memory, and written to the file whenever the sink is
+/// [flushed](crate::flush).
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{set_sink, FoldedStackSink};
+///
+/// set_sink(FoldedStackSink::new("timings.folded"));
+/// // ...then `inferno-flamegraph timings.folded > flamegraph.svg`
+/// ```
+#[derive(Debug)]
+pub struct FoldedStackSink {
+    file: OutputFile,
+    stacks: Mutex<Stacks>,
+}
+
+#[derive(Debug, Default)]
+struct Stacks {
+    /// Self time in nanoseconds, by folded stack.
+    weights: BTreeMap<String, u128>,
+    /// The tags currently open on each thread, outermost first.
+    open: HashMap<ThreadId, Vec<String>>,
+}
+
+impl FoldedStackSink {
+    /// Creates a sink that writes its stacks to the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: OutputFile::new(path.as_ref(), "folded stacks"),
+            stacks: Mutex::new(Stacks::default()),
+        }
+    }
+
+    /// Writes the stacks collected so far to `writer`, one per line, sorted.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let stacks = self
+            .stacks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (stack, nanos) in &stacks.weights {
+            writeln!(writer, "{} {}", stack, nanos)?;
+        }
+        writer.flush()
+    }
+}
+
+impl Sink for FoldedStackSink {
+    fn record(&self, record: &TimingRecord) {
+        let mut stacks = self
+            .stacks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Records of a thread arrive in tree order, parents first, so the
+        // stack above this record is whatever is open at a lower depth.
+        let open = stacks.open.entry(record.thread.id()).or_default();
+        open.truncate(record.depth);
+        open.push(frame(record.tag));
+        let stack = open.join(";");
+        *stacks.weights.entry(stack).or_insert(0) += record.self_time.as_nanos();
+    }
+
+    fn flush(&self) {
+        self.file.write(|writer| self.write_to(writer));
+    }
+}
+
+/// Turns a tag into a frame name, replacing the characters that separate
+/// frames and lines in the folded format.
+fn frame(tag: &str) -> String {
+    tag.replace(';', ":").replace(['\n', '\r'], " ")
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "release_also")))]
+mod tests {
+    use super::*;
+    use crate::{timer, with_clock, MockClock};
+    use std::time::Duration;
+
+    #[test]
+    fn test_folded_stacks_are_weighted_by_self_time() {
+        let sink = FoldedStackSink::new("unused.folded");
+        let clock = MockClock::new();
+        let ms = Duration::from_millis;
+        let (_, records) = crate::sink::testing::capture(|| {
+            with_clock(clock.clone(), || {
+                for _ in 0..2 {
+                    timer!(# "frame" {
+                        clock.advance(ms(1));
+                        timer!(# "render" {
+                            clock.advance(ms(2));
+                            timer!(# "a;b" { clock.advance(ms(3)) });
+                        });
+                    });
+                }
+            })
+        });
+        for record in &records {
+            sink.record(record);
+        }
+
+        let mut output = Vec::new();
+        sink.write_to(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "frame 2000000\nframe;render 4000000\nframe;render;a:b 6000000\n"
+        );
+    }
+}
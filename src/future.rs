@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Timing of futures, independent of any async runtime.
+
+use crate::active::{self, ActiveSpan, FutureEntry};
+use crate::{clock, Callsite, Outcome, TimingRecord};
+use std::future::Future;
+use std::panic::Location;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How a timed future was polled.
+///
+/// Carried by the [`TimingRecord`] of futures timed with [`Timed`] or
+/// `timer_async!`, whose `duration` is the wall time from creation to
+/// completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PollStats {
+    /// Time spent inside `poll`, i.e. actually running rather than waiting.
+    pub busy: Duration,
+    /// How many times the future was polled.
+    pub polls: u64,
+    /// Time from creating the future to polling it for the first time.
+    pub first_poll: Duration,
+}
+
+/// A future that measures the future it wraps.
+///
+/// Resolves to the output of the inner future together with a
+/// [`TimingRecord`], like the tagged forms of `timer_silent!`. Create one
+/// with [`FutureExt::timed`], or use `timer_async!` to report the timing
+/// through the installed [`Sink`](crate::Sink) instead.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::FutureExt;
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     use std::task::{Context, Poll, Wake, Waker};
+/// #     struct Noop;
+/// #     impl Wake for Noop { fn wake(self: std::sync::Arc<Self>) {} }
+/// #     let waker = Waker::from(std::sync::Arc::new(Noop));
+/// #     let mut future = Box::pin(future);
+/// #     loop {
+/// #         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+/// #             return output;
+/// #         }
+/// #     }
+/// # }
+///
+/// let (result, record) = block_on(async { 1 + 1 }.timed().with_tag("add"));
+/// assert_eq!(result, 2);
+/// assert_eq!(record.tag, "add");
+/// let poll = record.poll.unwrap();
+/// assert_eq!(poll.polls, 1);
+/// assert!(poll.busy <= record.duration);
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timed<F> {
+    future: F,
+    tag: &'static str,
+    file: &'static str,
+    line: u32,
+    column: u32,
+    module: &'static str,
+    created: Instant,
+    first_poll: Option<Instant>,
+    busy: Duration,
+    polls: u64,
+}
+
+impl<F: Future> Timed<F> {
+    #[doc(hidden)]
+    pub fn new(
+        future: F,
+        tag: &'static str,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        module: &'static str,
+    ) -> Self {
+        Self {
+            future,
+            tag,
+            file,
+            line,
+            column,
+            module,
+            created: clock::now(),
+            first_poll: None,
+            busy: Duration::ZERO,
+            polls: 0,
+        }
+    }
+
+    /// Sets the tag of the timing, which is `"Timer"` by default.
+    pub fn with_tag(mut self, tag: &'static str) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Sets the module path of the timing, which is empty by default. Pass
+    /// `module_path!()` so that [module directives](crate::set_filter) and
+    /// sinks see where the future was timed.
+    pub fn with_module(mut self, module: &'static str) -> Self {
+        self.module = module;
+        self
+    }
+}
+
+impl<F> Timed<F> {
+    /// The record of the future, as if it finished at `end`. A future that
+    /// was never polled waited its whole duration for its first poll.
+    fn record(&self, end: Instant) -> TimingRecord {
+        let mut record = TimingRecord::new(
+            self.tag,
+            self.file,
+            self.line,
+            self.column,
+            self.module,
+            self.created,
+            end.saturating_duration_since(self.created),
+        );
+        record.poll = Some(PollStats {
+            busy: self.busy,
+            polls: self.polls,
+            first_poll: self
+                .first_poll
+                .unwrap_or(end)
+                .saturating_duration_since(self.created),
+        });
+        record
+    }
+}
+
+impl<F: Future> Future for Timed<F> {
+    type Output = (F::Output, TimingRecord);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned: it is never moved out of
+        // `Timed`, and `Timed` does not implement `Drop` or `Unpin` by hand.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        let poll_start = clock::now();
+        this.first_poll.get_or_insert(poll_start);
+        let poll = future.poll(cx);
+        let poll_end = clock::now();
+        this.busy += poll_end.saturating_duration_since(poll_start);
+        this.polls += 1;
+
+        match poll {
+            Poll::Ready(output) => Poll::Ready((output, this.record(poll_end))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Extension methods for timing any [`Future`].
+pub trait FutureExt: Future + Sized {
+    /// Wraps the future in a [`Timed`] that measures its wall time, busy
+    /// time, poll count and time to first poll.
+    ///
+    /// The timing is tagged `"Timer"` unless [`Timed::with_tag`] is used,
+    /// and carries the location of this call. Its module path is left empty
+    /// unless [`Timed::with_module`] is used.
+    #[track_caller]
+    fn timed(self) -> Timed<Self> {
+        let location = Location::caller();
+        Timed::new(
+            self,
+            "Timer",
+            location.file(),
+            location.line(),
+            location.column(),
+            "",
+        )
+    }
+}
+
+impl<F: Future> FutureExt for F {}
+
+/// Times `future` and dispatches the record once it completes, if the filter
+/// enables `callsite`. This is what `timer_async!` expands to.
+///
+/// The timing starts here rather than on the first poll, so that the time
+/// the future waited to be polled is measured. Until it completes or is
+/// dropped, the future is listed by [`active`](crate::active). A future
+/// dropped before it completes is reported as [`Outcome::Cancelled`], or as
+/// [`Outcome::Panicked`] if it is dropped by a panic.
+#[doc(hidden)]
+pub fn instrument<F: Future>(callsite: &'static Callsite, future: F) -> Instrumented<F> {
+    if !callsite.is_enabled() {
+        return Instrumented {
+            state: State::Disabled(future),
+            entry: None,
+            done: false,
+        };
+    }
+    let timed = Timed::new(
+        future,
+        callsite.tag,
+        callsite.file,
+        callsite.line,
+        callsite.column,
+        callsite.module,
+    );
+    let entry = if active::is_tracking() {
+        Some(FutureEntry::enter(ActiveSpan {
+            id: crate::span::next_id(),
+            tag: callsite.tag,
+            label: None,
+            file: callsite.file,
+            line: callsite.line,
+            column: callsite.column,
+            module: callsite.module,
+            start: timed.created,
+        }))
+    } else {
+        None
+    };
+    Instrumented {
+        state: State::Enabled(timed),
+        entry,
+        done: false,
+    }
+}
+
+/// The future returned by [`instrument`].
+#[doc(hidden)]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Instrumented<F> {
+    state: State<F>,
+    /// The entry of the future in the registry of running blocks.
+    entry: Option<FutureEntry>,
+    /// Whether the future completed and was reported.
+    done: bool,
+}
+
+#[derive(Debug)]
+enum State<F> {
+    Enabled(Timed<F>),
+    Disabled(F),
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the future in `state` is structurally pinned: it is never
+        // moved out, `Instrumented` does not implement `Unpin` by hand, and
+        // its `Drop` does not move it. `entry` and `done` are not pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        match &mut this.state {
+            State::Enabled(timed) => match unsafe { Pin::new_unchecked(timed) }.poll(cx) {
+                Poll::Ready((output, record)) => {
+                    this.entry = None;
+                    this.done = true;
+                    crate::dispatch(&record);
+                    Poll::Ready(output)
+                }
+                Poll::Pending => Poll::Pending,
+            },
+            State::Disabled(future) => unsafe { Pin::new_unchecked(future) }.poll(cx),
+        }
+    }
+}
+
+impl<F> Drop for Instrumented<F> {
+    /// Reports a future dropped before it completed.
+    fn drop(&mut self) {
+        if let (State::Enabled(timed), false) = (&self.state, self.done) {
+            let mut record = timed.record(clock::now());
+            record.outcome = if thread::panicking() {
+                Outcome::Panicked
+            } else {
+                Outcome::Cancelled
+            };
+            crate::dispatch(&record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+    use std::thread;
+
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut context = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Stays pending until `delay` has passed, waking itself from another thread.
+    struct Sleep(Option<Instant>, Duration);
+
+    impl Future for Sleep {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let delay = self.1;
+            let deadline = *self.0.get_or_insert_with(|| Instant::now() + delay);
+            if Instant::now() >= deadline {
+                return Poll::Ready(());
+            }
+            let waker = cx.waker().clone();
+            thread::spawn(move || {
+                thread::sleep(delay);
+                waker.wake();
+            });
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn test_timed_separates_busy_and_wall_time() {
+        let (result, record) = block_on(
+            async {
+                Sleep(None, Duration::from_millis(20)).await;
+                42
+            }
+            .timed()
+            .with_tag("sleep")
+            .with_module(module_path!()),
+        );
+
+        assert_eq!(result, 42);
+        assert_eq!(record.tag, "sleep");
+        assert_eq!(record.file, file!());
+        assert_eq!(record.module, module_path!());
+        let poll = record.poll.unwrap();
+        assert!(poll.polls >= 2);
+        assert!(record.duration >= Duration::from_millis(20));
+        assert!(poll.busy < record.duration);
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    fn test_timer_async_dispatches_to_sink() {
+        let (result, records) = crate::sink::testing::capture(|| {
+            block_on(crate::timer_async!(# fetch {
+                Sleep(None, Duration::from_millis(5)).await;
+                "done"
+            }))
+        });
+
+        assert_eq!(result, "done");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tag, "fetch");
+        assert_eq!(records[0].module, module_path!());
+        assert!(records[0].poll.unwrap().polls >= 2);
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    fn test_timer_async_times_from_creation() {
+        let clock = crate::MockClock::new();
+        let (_, records) = crate::sink::testing::capture(|| {
+            crate::with_clock(clock.clone(), || {
+                let future = crate::timer_async!(# deferred {
+                    clock.advance(Duration::from_millis(2));
+                });
+                clock.advance(Duration::from_millis(50));
+                block_on(future);
+            })
+        });
+
+        let poll = records[0].poll.unwrap();
+        assert_eq!(poll.first_poll, Duration::from_millis(50));
+        assert_eq!(poll.busy, Duration::from_millis(2));
+        assert_eq!(records[0].duration, Duration::from_millis(52));
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    fn test_timer_async_reports_cancellation() {
+        let clock = crate::MockClock::new();
+        let (_, records) = crate::sink::testing::capture(|| {
+            crate::with_clock(clock.clone(), || {
+                let mut polled = Box::pin(crate::timer_async!(# "polled" {
+                    std::future::pending::<()>().await;
+                }));
+                let waker = Waker::from(Arc::new(Unpark(thread::current())));
+                assert!(polled
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_pending());
+                let unpolled = crate::timer_async!(# "unpolled" {});
+                clock.advance(Duration::from_millis(3));
+                drop(polled);
+                drop(unpolled);
+            })
+        });
+
+        let outcomes: Vec<_> = records
+            .iter()
+            .map(|r| (r.tag, r.outcome, r.duration, r.poll.unwrap().polls))
+            .collect();
+        let millis = Duration::from_millis(3);
+        assert_eq!(
+            outcomes,
+            [
+                ("polled", Outcome::Cancelled, millis, 1),
+                ("unpolled", Outcome::Cancelled, millis, 0),
+            ]
+        );
+        assert_eq!(records[1].poll.unwrap().first_poll, millis);
+        assert!(records[0].to_string().ends_with(" (cancelled)"));
+    }
+}
@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A fixed-size, log-bucketed latency histogram.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Bits of precision kept within each power of two. Every bucket is at most
+/// 1/32 (about 3%) of its value wide.
+const PRECISION: u32 = 6;
+const HALF: u64 = 1 << (PRECISION - 1);
+const BUCKETS: usize = ((64 - PRECISION as u64) * HALF + (1 << PRECISION)) as usize;
+
+/// A histogram of durations with bounded memory, in the style of HdrHistogram.
+///
+/// Durations are recorded in nanoseconds into log-linear buckets: values below
+/// 64ns are kept exactly, larger ones with a relative error of at most about 3%.
+/// A histogram always occupies the same amount of memory, no matter how many
+/// values it holds or how large they are.
+///
+/// Every call site of `timer!` and `timer_silent!` keeps one; see
+/// [`TimerStats::histogram`](crate::TimerStats::histogram) and
+/// [`histogram`](crate::histogram()).
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::Histogram;
+/// use std::time::Duration;
+///
+/// let mut a = Histogram::new();
+/// let mut b = Histogram::new();
+/// for millis in 1..=50 {
+///     a.record(Duration::from_millis(millis));
+/// }
+/// for millis in 51..=100 {
+///     b.record(Duration::from_millis(millis));
+/// }
+///
+/// a.merge(&b);
+/// assert_eq!(a.count(), 100);
+/// assert!(a.percentile(50.0) >= Duration::from_millis(49));
+/// assert!(a.percentile(50.0) <= Duration::from_millis(52));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    count: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Histogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; BUCKETS],
+            count: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Records one duration.
+    pub fn record(&mut self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.counts[index_of(nanos)] += 1;
+        self.count += 1;
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// Adds every value recorded in `other` to this histogram.
+    pub fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// The number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The smallest recorded value, or zero if the histogram is empty.
+    pub fn min(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos(self.min)
+        }
+    }
+
+    /// The largest recorded value.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// Returns the value below which `percentile` percent of the recorded
+    /// values fall, e.g. `percentile(99.0)` for p99.
+    ///
+    /// The result is the upper bound of the bucket holding that value, clamped
+    /// to the recorded range. An empty histogram returns zero.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        let quantile = (percentile / 100.0).clamp(0.0, 1.0);
+        let rank = ((quantile * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let value = highest_equivalent(index).max(self.min).min(self.max);
+                return Duration::from_nanos(value);
+            }
+        }
+        self.max()
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("p50", &self.percentile(50.0))
+            .field("p99", &self.percentile(99.0))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+fn index_of(value: u64) -> usize {
+    let msb = 63 - (value | 1).leading_zeros();
+    if msb < PRECISION {
+        return value as usize;
+    }
+    let shift = msb - PRECISION + 1;
+    (u64::from(shift) * HALF + (value >> shift)) as usize
+}
+
+fn highest_equivalent(index: usize) -> u64 {
+    let index = index as u64;
+    if index < (1 << PRECISION) {
+        return index;
+    }
+    let shift = index / HALF - 1;
+    let top = index - shift * HALF;
+    // The top bucket ends at `u64::MAX`, where the shift wraps to zero.
+    ((top + 1) << shift).wrapping_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_round_trip() {
+        for value in (0..100_000).chain([u64::MAX / 3, u64::MAX - 1, u64::MAX]) {
+            let index = index_of(value);
+            assert!(index < BUCKETS);
+            let high = highest_equivalent(index);
+            assert!(high >= value, "{} -> {} -> {}", value, index, high);
+            assert!(high - value <= value / 32, "{} -> {}", value, high);
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = Histogram::new();
+        for micros in 1..=1000 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.min(), Duration::from_micros(1));
+        assert_eq!(histogram.max(), Duration::from_micros(1000));
+        for (percentile, expected) in [(50.0, 500), (90.0, 900), (99.0, 990), (100.0, 1000)] {
+            let value = histogram.percentile(percentile).as_nanos() as f64;
+            let expected = Duration::from_micros(expected).as_nanos() as f64;
+            assert!(
+                (value - expected).abs() / expected < 0.035,
+                "p{}",
+                percentile
+            );
+        }
+    }
+}
@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Human-readable formatting of durations.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+/// The unit durations are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// The largest unit in which the value is at least 1, e.g. `1.23 ms`
+    /// rather than `1230 µs`.
+    Auto,
+    /// Nanoseconds, `ns`.
+    Nanos,
+    /// Microseconds, `µs`.
+    Micros,
+    /// Milliseconds, `ms`.
+    Millis,
+    /// Seconds, `s`.
+    Secs,
+}
+
+impl Unit {
+    const ALL: [Unit; 5] = [
+        Unit::Auto,
+        Unit::Nanos,
+        Unit::Micros,
+        Unit::Millis,
+        Unit::Secs,
+    ];
+
+    /// The suffix printed after values in this unit.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Unit::Auto => "",
+            Unit::Nanos => "ns",
+            Unit::Micros => "µs",
+            Unit::Millis => "ms",
+            Unit::Secs => "s",
+        }
+    }
+
+    fn nanos(self) -> f64 {
+        match self {
+            Unit::Auto | Unit::Nanos => 1.0,
+            Unit::Micros => 1e3,
+            Unit::Millis => 1e6,
+            Unit::Secs => 1e9,
+        }
+    }
+
+    fn for_duration(duration: Duration) -> Unit {
+        match duration.as_nanos() {
+            0..=999 => Unit::Nanos,
+            1_000..=999_999 => Unit::Micros,
+            1_000_000..=999_999_999 => Unit::Millis,
+            _ => Unit::Secs,
+        }
+    }
+}
+
+/// The error returned when parsing an unknown [`Unit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUnitError(String);
+
+impl fmt::Display for ParseUnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown duration unit `{}`, expected one of auto, ns, us, ms or s",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseUnitError {}
+
+/// Parses `auto`, `ns`, `us` (or `µs`), `ms` or `s`.
+impl FromStr for Unit {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "auto" => Ok(Unit::Auto),
+            "ns" => Ok(Unit::Nanos),
+            "us" | "µs" => Ok(Unit::Micros),
+            "ms" => Ok(Unit::Millis),
+            "s" => Ok(Unit::Secs),
+            other => Err(ParseUnitError(other.to_string())),
+        }
+    }
+}
+
+/// Not yet resolved from the `QUICK_TIMER_UNIT` build-time variable.
+const UNSET: u8 = u8::MAX;
+
+static UNIT: AtomicU8 = AtomicU8::new(UNSET);
+static DIGITS: AtomicU8 = AtomicU8::new(3);
+
+/// Sets the unit that timings are printed in. The default is [`Unit::Auto`],
+/// unless the `QUICK_TIMER_UNIT` environment variable was set to a unit
+/// when this crate was built.
+pub fn set_unit(unit: Unit) {
+    let index = Unit::ALL.iter().position(|u| *u == unit).unwrap_or(0);
+    UNIT.store(index as u8, Ordering::Relaxed);
+}
+
+/// Returns the unit that timings are printed in.
+pub fn unit() -> Unit {
+    match UNIT.load(Ordering::Relaxed) {
+        UNSET => {
+            let unit = option_env!("QUICK_TIMER_UNIT")
+                .and_then(|unit| unit.parse().ok())
+                .unwrap_or(Unit::Auto);
+            set_unit(unit);
+            unit
+        }
+        index => Unit::ALL[usize::from(index)],
+    }
+}
+
+/// Sets how many significant digits timings are printed with, at least 1.
+/// The default is 3, e.g. `1.23 ms` or `456 µs`.
+pub fn set_significant_digits(digits: u8) {
+    DIGITS.store(digits.max(1), Ordering::Relaxed);
+}
+
+/// Returns how many significant digits timings are printed with.
+pub fn significant_digits() -> u8 {
+    DIGITS.load(Ordering::Relaxed)
+}
+
+/// Displays a duration in a human-friendly unit, e.g. `1.23 ms`.
+///
+/// Unless overridden with [`unit`](HumanDuration::unit) and
+/// [`significant_digits`](HumanDuration::significant_digits), the settings of
+/// [`set_unit`] and [`set_significant_digits`] are used. This is how
+/// `timer!` prints its timings.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{HumanDuration, Unit};
+/// use std::time::Duration;
+///
+/// let duration = Duration::from_nanos(1_234_567);
+/// assert_eq!(HumanDuration::new(duration).unit(Unit::Auto).significant_digits(3).to_string(), "1.23 ms");
+/// assert_eq!(HumanDuration::new(duration).unit(Unit::Micros).significant_digits(3).to_string(), "1235 µs");
+/// assert_eq!(HumanDuration::new(duration).unit(Unit::Secs).significant_digits(2).to_string(), "0.0012 s");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration {
+    duration: Duration,
+    unit: Option<Unit>,
+    digits: Option<u8>,
+}
+
+impl HumanDuration {
+    /// Wraps `duration` for display.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            unit: None,
+            digits: None,
+        }
+    }
+
+    /// Prints in `unit` instead of the unit set by [`set_unit`].
+    pub fn unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Prints with `digits` significant digits instead of the number set by
+    /// [`set_significant_digits`].
+    pub fn significant_digits(mut self, digits: u8) -> Self {
+        self.digits = Some(digits.max(1));
+        self
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self::new(duration)
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chosen = self.unit.unwrap_or_else(unit);
+        let auto = chosen == Unit::Auto;
+        let mut unit = if auto {
+            Unit::for_duration(self.duration)
+        } else {
+            chosen
+        };
+        let digits = i32::from(self.digits.unwrap_or_else(significant_digits));
+
+        // Whole digits are never dropped; only decimals are added to reach
+        // the requested number of significant digits.
+        let decimals = |value: f64, unit: Unit| {
+            if value == 0.0 || unit == Unit::Nanos {
+                0
+            } else {
+                let magnitude = value.log10().floor() as i32 + 1;
+                (digits - magnitude).max(0) as usize
+            }
+        };
+        let text = loop {
+            let value = self.duration.as_nanos() as f64 / unit.nanos();
+            let places = decimals(value, unit);
+            let text = format!("{:.*}", places, value);
+            // Rounding may carry into a new digit, e.g. `999.9` to `1000`,
+            // which moves to the next unit or needs one decimal fewer.
+            let rounded: f64 = text.parse().unwrap_or(value);
+            if auto && rounded >= 1000.0 && unit != Unit::Secs {
+                unit = match unit {
+                    Unit::Nanos => Unit::Micros,
+                    Unit::Micros => Unit::Millis,
+                    _ => Unit::Secs,
+                };
+                continue;
+            }
+            let fewer = decimals(rounded, unit);
+            if fewer < places {
+                break format!("{:.*}", fewer, value);
+            }
+            break text;
+        };
+        f.pad(&format!("{} {}", text, unit.suffix()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn show(nanos: u64) -> String {
+        HumanDuration::new(Duration::from_nanos(nanos))
+            .unit(Unit::Auto)
+            .significant_digits(3)
+            .to_string()
+    }
+
+    #[test]
+    fn test_auto_units() {
+        assert_eq!(show(0), "0 ns");
+        assert_eq!(show(999), "999 ns");
+        assert_eq!(show(1_000), "1.00 µs");
+        assert_eq!(show(12_345), "12.3 µs");
+        assert_eq!(show(456_789), "457 µs");
+        assert_eq!(show(1_234_567), "1.23 ms");
+        assert_eq!(show(61_500_000_000), "61.5 s");
+        assert_eq!(show(1_234_000_000_000), "1234 s");
+        // Rounding up to a new digit keeps the number of significant digits.
+        assert_eq!(show(999_999), "1.00 ms");
+        assert_eq!(show(99_950), "100 µs");
+        assert_eq!(show(9_999), "10.0 µs");
+        assert_eq!(show(999_999_999), "1.00 s");
+        assert_eq!(
+            format!(
+                "{:>10}",
+                HumanDuration::new(Duration::ZERO).unit(Unit::Nanos)
+            ),
+            "      0 ns"
+        );
+    }
+
+    #[test]
+    fn test_parse_unit() {
+        assert_eq!("µs".parse(), Ok(Unit::Micros));
+        assert_eq!(" ms ".parse(), Ok(Unit::Millis));
+        assert!("minutes".parse::<Unit>().is_err());
+    }
+}
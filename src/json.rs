@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A sink writing timings as JSON Lines.
+
+use crate::{FieldValue, Outcome, Sink, TimingRecord};
+use std::fmt::{self, Write as _};
+use std::fs::OpenOptions;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Writes every record as one line of JSON, for log ingestion and other
+/// tooling that wants structured data.
+///
+/// Each line is an object with these fields:
+///
+/// | field | value |
+/// |-------|-------|
+/// | `tag`, `file`, `line`, `column`, `module` | where the timer is |
+/// | `label` | for tags with format arguments only, the formatted tag |
+/// | `thread` | the thread name, or its id if it has none |
+/// | `start_unix_ns` | when the block started, in nanoseconds since the Unix epoch |
+/// | `start_ns` | when the block started, relative to the first time the process read the clock |
+/// | `duration_ns`, `self_ns` | the total and self time, in nanoseconds |
+/// | `depth` | how many timers enclose this one |
+/// | `outcome` | `"completed"`, `"early_exit"`, `"panicked"` or `"cancelled"` |
+/// | `busy_ns`, `polls` | for futures only, how they were polled |
+/// | `threshold_ns`, `slow` | for timers with a threshold only, the threshold and whether the timing was slower |
+/// | `fields` | for timers with fields only, an object of their values |
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{set_sink, JsonSink};
+///
+/// set_sink(JsonSink::file("timings.jsonl")?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct JsonSink {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonSink {
+    /// Writes JSON Lines to `writer`.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            out: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Writes JSON Lines to stdout.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Writes JSON Lines to stderr.
+    pub fn stderr() -> Self {
+        Self::new(io::stderr())
+    }
+
+    /// Appends JSON Lines to the file at `path`, creating it if needed.
+    ///
+    /// Every line is written out as soon as it is complete, so nothing is lost
+    /// if the process exits without flushing.
+    pub fn file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(LineWriter::new(file)))
+    }
+}
+
+impl fmt::Debug for JsonSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonSink").finish_non_exhaustive()
+    }
+}
+
+impl Sink for JsonSink {
+    fn record(&self, record: &TimingRecord) {
+        let mut line = to_json(record);
+        line.push('\n');
+        let mut out = self
+            .out
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Like `println!`, one write per record, so lines never interleave.
+        let _ = out.write_all(line.as_bytes());
+    }
+
+    fn flush(&self) {
+        let _ = self
+            .out
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .flush();
+    }
+}
+
+/// Encodes `record` as a single-line JSON object.
+fn to_json(record: &TimingRecord) -> String {
+    let start_unix_ns = record
+        .start_time()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos());
+    let thread = match record.thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", record.thread.id()),
+    };
+    let outcome = match record.outcome {
+        Outcome::Completed => "completed",
+        Outcome::EarlyExit => "early_exit",
+        Outcome::Panicked => "panicked",
+        Outcome::Cancelled => "cancelled",
+    };
+
+    let mut json = String::with_capacity(256);
+    json.push_str("{\"tag\":");
+    write_string(&mut json, record.tag);
+    if let Some(label) = &record.label {
+        json.push_str(",\"label\":");
+        write_string(&mut json, label);
+    }
+    json.push_str(",\"file\":");
+    write_string(&mut json, record.file);
+    let _ = write!(
+        json,
+        ",\"line\":{},\"column\":{},\"module\":",
+        record.line, record.column
+    );
+    write_string(&mut json, record.module);
+    json.push_str(",\"thread\":");
+    write_string(&mut json, &thread);
+    let _ = write!(
+        json,
+        ",\"start_unix_ns\":{},\"start_ns\":{},\"duration_ns\":{},\"self_ns\":{},\"depth\":{},\"outcome\":\"{}\"",
+        start_unix_ns,
+        record.start.as_nanos(),
+        record.duration.as_nanos(),
+        record.self_time.as_nanos(),
+        record.depth,
+        outcome,
+    );
+    if let Some(poll) = &record.poll {
+        let _ = write!(
+            json,
+            ",\"busy_ns\":{},\"polls\":{}",
+            poll.busy.as_nanos(),
+            poll.polls
+        );
+    }
+    if let Some(threshold) = record.threshold {
+        let _ = write!(
+            json,
+            ",\"threshold_ns\":{},\"slow\":{}",
+            threshold.as_nanos(),
+            record.is_slow()
+        );
+    }
+    if !record.fields.is_empty() {
+        json.push_str(",\"fields\":{");
+        write_fields(&mut json, &record.fields);
+        json.push('}');
+    }
+    json.push('}');
+    json
+}
+
+/// Appends `fields` to `json` as the members of an object, without braces.
+pub(crate) fn write_fields(json: &mut String, fields: &[(&'static str, FieldValue)]) {
+    for (index, (key, value)) in fields.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        write_string(json, key);
+        json.push(':');
+        match value {
+            FieldValue::Bool(value) => {
+                let _ = write!(json, "{}", value);
+            }
+            FieldValue::I64(value) => {
+                let _ = write!(json, "{}", value);
+            }
+            FieldValue::U64(value) => {
+                let _ = write!(json, "{}", value);
+            }
+            // JSON has no infinities or NaN.
+            FieldValue::F64(value) if !value.is_finite() => json.push_str("null"),
+            FieldValue::F64(value) => {
+                let _ = write!(json, "{:?}", value);
+            }
+            FieldValue::Str(value) => write_string(json, value),
+        }
+    }
+}
+
+/// Appends `value` to `json` as a quoted, escaped JSON string.
+pub(crate) fn write_string(json: &mut String, value: &str) {
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if u32::from(ch) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", u32::from(ch));
+            }
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_string_escapes() {
+        let mut json = String::new();
+        write_string(&mut json, "a \"b\"\\\n\u{1}µ");
+        assert_eq!(json, r#""a \"b\"\\\n\u0001µ""#);
+    }
+
+    #[test]
+    fn test_json_lines() {
+        // The record would otherwise take a default threshold set by a test.
+        let _lock = crate::sink::testing::lock();
+        let buffer = Buffer::default();
+        let sink = JsonSink::new(buffer.clone());
+        let mut record = TimingRecord::new(
+            "load \"config\"",
+            "src/main.rs",
+            7,
+            5,
+            "app",
+            Instant::now(),
+            Duration::from_micros(1500),
+        );
+        record.self_time = Duration::from_micros(500);
+        sink.record(&record);
+        sink.record(&record);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(
+            r#"{"tag":"load \"config\"","file":"src/main.rs","line":7,"column":5,"module":"app","thread":"#
+        ));
+        assert!(lines[0].contains(
+            r#","duration_ns":1500000,"self_ns":500000,"depth":0,"outcome":"completed"}"#
+        ));
+        assert!(!lines[0].contains("polls"));
+        assert!(!lines[0].contains("fields"));
+    }
+
+    #[test]
+    fn test_label_and_fields() {
+        let mut record = TimingRecord::new(
+            "load {}",
+            "src/main.rs",
+            7,
+            5,
+            "app",
+            Instant::now(),
+            Duration::from_micros(1500),
+        );
+        record.label = Some("load a.txt".to_string());
+        record.fields = vec![
+            ("rows", FieldValue::U64(3)),
+            ("delta", FieldValue::I64(-2)),
+            ("ratio", FieldValue::F64(1.0)),
+            ("nan", FieldValue::F64(f64::NAN)),
+            ("cached", FieldValue::Bool(false)),
+            ("user", FieldValue::Str("\"ann\"".to_string())),
+        ];
+        let json = to_json(&record);
+        assert!(json.starts_with(r#"{"tag":"load {}","label":"load a.txt","file":"#));
+        assert!(json.ends_with(
+            r#","fields":{"rows":3,"delta":-2,"ratio":1.0,"nan":null,"cached":false,"user":"\"ann\""}}"#
+        ));
+    }
+}
@@ -41,12 +41,12 @@
 *     });
 *
 *     timer! {
-*         # Tag
+*         ## Tag
 *         println!("You can do somethings here")
 *     }
 *
 *     timer! {
-*         # "A Tag"
+*         ## "A Tag"
 *         println!("You can do somethings here")
 *     }
 *
@@ -79,13 +79,282 @@
 *     );
 * }
 * ```
+*
+* ## Output
+*
+* Every timing made by `timer!` is handed to a [`Sink`] as a [`TimingRecord`].
+* By default records are printed to stdout; use [`set_sink`] to send them
+* somewhere else, for example [`StderrSink`] or [`NullSink`]. [`JsonSink`]
+* writes them as JSON Lines, for tools that want structured data, and
+* [`ChromeTraceSink`] writes a trace that `chrome://tracing` and Perfetto can open.
+* [`FoldedStackSink`] sums nested timings into folded stacks for flamegraphs,
+* and [`SpeedscopeSink`] writes per-thread timelines for speedscope. These
+* three write their file when flushed, and when the process exits on Unix and
+* Windows. Elsewhere, or if the process may abort, call [`flush`] before `main`
+* returns, or keep a [`FlushGuard`] alive in `main`.
+*
+* ## Dynamic Tags and Fields
+*
+* The `tag:` form of `timer!` also takes format arguments after the tag, and
+* key-value fields, which are only evaluated when the timer is enabled:
+*
+* ```rust
+* use quick_timer::timer;
+*
+* let (path, user) = ("data.bin", 7);
+* timer!(tag: "load {}", path; fields: user = user, cached = false; block: {
+*     // ...
+* });
+* ```
+*
+* Sinks receive the formatted tag as [`TimingRecord::label`], and the fields,
+* as [`FieldValue`]s, in [`TimingRecord::fields`]. Statistics are still kept
+* per call site, under the unformatted tag.
+*
+* ## Attribute Macro
+*
+* With the `macros` feature enabled, `#[timed]` times every call of a function,
+* using the path of the function as the tag:
+*
+* ```toml
+* [dependencies]
+* quick-timer = { version = "0.1.2", features = ["macros"] }
+* ```
+*
+* ```rust,ignore
+* use quick_timer::timed;
+*
+* #[timed]
+* fn load() -> Vec<u8> {
+*     std::fs::read("data.bin").unwrap_or_default()
+* }
+*
+* #[timed("parse input")]
+* fn parse(input: &[u8]) -> usize {
+*     input.len()
+* }
+* ```
+*
+* The code `#[timed]` generates refers to the crate as `::quick_timer`, so the
+* dependency cannot be renamed in `Cargo.toml`.
+*
+* ## Async
+*
+* `timer_async!` times async code and reports through the installed [`Sink`]
+* like `timer!`. It evaluates to a future, and its record also tells how much
+* of the wall time was spent being polled, how often, and how long the future
+* waited to be polled first. [`FutureExt::timed`] does the same for any future,
+* returning the [`TimingRecord`] instead of reporting it.
+*
+* ```rust
+* use quick_timer::timer_async;
+*
+* async fn fetch() -> u32 {
+*     timer_async!(# "fetch" {
+*         // something.await
+*         42
+*     })
+*     .await
+* }
+* ```
+*
+* ## Nesting
+*
+* `timer!` blocks nested on the same thread are reported together when the
+* outermost block finishes, as an indented tree, though they are aggregated
+* into the [statistics](#statistics) as soon as they finish. Blocks with nested
+* timers also report their self time, which leaves out the time spent in
+* nested blocks:
+*
+* ```text
+* in src/main.rs line 3 load: 120 ms (self 20.1 ms)
+*   in src/main.rs line 4 read: 80.2 ms
+*   in src/main.rs line 7 parse: 19.7 ms
+* ```
+*
+* A thread holds back at most 4096 records. Past that, the records held so far
+* are reported, and the rest of the tree is reported as each block finishes,
+* nested blocks first, until the outermost block finishes.
+*
+* ## Watchdog
+*
+* A `timer!` block is only reported once it finishes, so a block that hangs is
+* never reported. A [`Watchdog`] runs on a background thread, and warns on
+* stderr about blocks that are still running after a while, with their tag,
+* call site and thread:
+*
+* ```text
+* warning: in src/main.rs line 12 sync: still running after 5.00 s (thread worker-2)
+* ```
+*
+* To see which blocks are running right now, and for how long, call
+* [`track_active`] early on, then [`active`], or [`report_active`] to print them
+* to stderr. Async blocks timed with [`timer_async!`] are watched and listed
+* too. Timers only keep track of the running blocks once the watchdog or
+* [`track_active`] asks for it; until then, [`active`] lists nothing:
+*
+* ```text
+* 2 active timers:
+*   in src/main.rs line 8 job 42: running for 3.12 s (thread worker-1)
+*     in src/main.rs line 10 sync: running for 3.10 s (thread worker-1)
+* ```
+*
+* ## Without Macros
+*
+* [`Stopwatch`] measures time with explicit `start`, `pause`, `resume`, `lap`
+* and `stop` calls. [`TimerGuard`] times a region until it is dropped and then
+* reports it like `timer!`, which helps when the region does not fit in one
+* block. Create it with [`TimerGuard::with_module`] and `module_path!()` for
+* [module filters](set_filter) to apply to it.
+*
+* ## Clocks
+*
+* All timers read the time through a [`Clock`]. The default [`InstantClock`]
+* uses [`Instant::now`](std::time::Instant::now); tests can install a
+* [`MockClock`] with [`set_clock`], or on the current thread only with
+* [`with_clock`], and advance it by hand to get exact durations.
+*
+* ## Early Exits
+*
+* A `timer!` block is reported however it is left: by running to the end, by
+* `?`, `return` or `break`, or by panicking. [`TimingRecord::outcome`] tells
+* which one happened.
+*
+* ## Filtering
+*
+* Timers can be turned on and off at runtime, without rebuilding, through the
+* `QUICK_TIMER` environment variable. It takes a comma-separated list of
+* directives, like `env_logger`: `QUICK_TIMER=off` silences every timer, and
+* `QUICK_TIMER=off,db*=on,my_app::render=on` keeps only the timers whose tag
+* starts with `db` and those in the `my_app::render` module. Use [`set_filter`]
+* to change the filter from code.
+*
+* Each timer remembers whether the filter enables it, so a disabled timer costs
+* a single atomic load, and does not read the clock.
+*
+* ## Thresholds
+*
+* To only hear about slow timings, give `timer!` a threshold, such as
+* `timer!(tag: "query", threshold: 5ms, block: { ... })`. Timings no slower
+* than it are aggregated into the statistics but not reported to the [`Sink`];
+* slower timings are reported as warnings. A block below its threshold is still
+* reported when a block nested in it was, so that the nested block is shown
+* under it. A default threshold for every timer can be set with the
+* `QUICK_TIMER_MIN` environment variable, e.g. `QUICK_TIMER_MIN=5ms`, or with
+* [`set_threshold`]; `threshold: None` opts a timer out of the default.
+*
+* ## Units
+*
+* Durations are printed in the unit that fits them best, with three
+* significant digits: `812 ns`, `12.3 µs`, `1.23 ms` or `2.50 s`. Use
+* [`set_unit`] and [`set_significant_digits`] to change that at runtime, or set
+* the `QUICK_TIMER_UNIT` environment variable (`ns`, `us`, `ms` or `s`) when
+* building to change the default unit. [`HumanDuration`] formats any
+* [`Duration`](std::time::Duration) the same way.
+*
+* ## Benchmarks
+*
+* `timer_silent!` measures a single run, which is too coarse for code taking
+* microseconds or less. [`bench!`] and [`bench()`] run code repeatedly after a
+* warmup, and summarize the runs as [`BenchStats`]: mean, median, standard
+* deviation, min, max, median absolute deviation and outliers. Configure the
+* runs with [`Bench`], and hide inputs from the optimizer with [`black_box`].
+*
+* To find out which of several variants is faster, [`compare!`] benchmarks them
+* in turns and reports the speedup of each over the first, with a confidence
+* interval and a significance test.
+*
+* [`Bench::scaling`] benchmarks a function over several input sizes, and fits the
+* times to candidate [`Complexity`] classes, such as `O(n)` or `O(n log n)`, to tell
+* how it scales.
+*
+* ## Performance Assertions
+*
+* [`timer_assert!`] and [`assert_faster_than!`] run a block and panic if it
+* took longer than a budget, such as `50ms`, optionally taking the best of
+* several runs to reduce noise. [`try_timer_assert!`] returns a
+* [`BudgetExceeded`] error instead of panicking.
+*
+* ## Statistics
+*
+* Every timing made by `timer!` is also aggregated per call site. Call [`report`]
+* to print a summary table, or [`snapshot`] to get the numbers as [`TimerStats`].
+* Each call site also keeps a [`Histogram`] of its durations, so percentiles such
+* as p99 can be read with [`histogram`].
+* Tagged `timer_silent!` calls are aggregated too; untagged ones, which only
+* return a `Duration`, are not. Call sites are told apart by tag, file, line and
+* column.
 */
 
+mod active;
+mod bench;
+mod budget;
+mod callsite;
+mod clock;
+mod compare;
+mod field;
+mod filter;
+mod folded;
+mod future;
+mod histogram;
+mod human;
+mod json;
+mod record;
+mod scaling;
+mod sink;
+mod span;
+mod speedscope;
+mod stats;
+mod stopwatch;
+mod threshold;
+mod trace;
+mod watchdog;
+
+pub use active::{active, report_active, track_active, write_active, ActiveTimer};
+pub use bench::{bench, black_box, Bench, BenchStats, Outliers};
+#[doc(hidden)]
+pub use budget::check_budget;
+pub use budget::BudgetExceeded;
+#[doc(hidden)]
+pub use callsite::Callsite;
+pub use clock::{now, set_clock, with_clock, Clock, InstantClock, MockClock};
+pub use compare::{Comparison, Relative, Variant, Verdict};
+pub use field::{FieldValue, ToFieldValue};
+pub use filter::{enabled, set_filter};
+pub use folded::FoldedStackSink;
+#[doc(hidden)]
+pub use future::{instrument, Instrumented};
+pub use future::{FutureExt, PollStats, Timed};
+pub use histogram::Histogram;
+pub use human::{
+    set_significant_digits, set_unit, significant_digits, unit, HumanDuration, ParseUnitError, Unit,
+};
+pub use json::JsonSink;
+pub use record::{Outcome, TimingRecord};
+pub use scaling::{Complexity, Fit, Scaling};
+pub use sink::{dispatch, flush, set_sink, FlushGuard, NullSink, Sink, StderrSink, StdoutSink};
+#[doc(hidden)]
+pub use span::SpanGuard;
+pub use speedscope::SpeedscopeSink;
+#[doc(hidden)]
+pub use stats::aggregate;
+pub use stats::{histogram, report, reset_stats, snapshot, write_report, TimerStats};
+pub use stopwatch::{Stopwatch, TimerGuard};
+#[doc(hidden)]
+pub use threshold::duration_literal;
+pub use threshold::{set_threshold, threshold};
+pub use trace::ChromeTraceSink;
+pub use watchdog::{Watchdog, WatchdogHandle};
+
+#[cfg(feature = "macros")]
+pub use quick_timer_macros::timed;
+
 #[macro_export]
 #[cfg(any(debug_assertions, feature = "release_also"))]
 /// Times the execution of a code block in debug mode or when `release_also` feature is enabled.
 ///
-/// This macro measures the execution time of a code block and prints the result to stdout.
+/// This macro measures the execution time of a code block and hands the result to the
+/// installed [`Sink`], which prints it to stdout unless [`set_sink`] was called.
 /// In release mode without the `release_also` feature, this macro is disabled and simply
 /// executes the code block without timing.
 ///
@@ -106,7 +375,7 @@
 ///
 /// // Alternative syntax
 /// timer! {
-///     # "My Tag"
+///     ## "My Tag"
 ///     // your code here
 /// }
 ///
@@ -114,6 +383,17 @@
 /// timer!(tag: "My Tag", block: {
 ///     // your code here
 /// });
+///
+/// // With a formatted tag and key-value fields
+/// let (id, rows) = (7, 120);
+/// timer!(tag: "Request {}", id; fields: rows = rows, cached = false; block: {
+///     // your code here
+/// });
+///
+/// // Only reported if it takes longer than 5 ms
+/// timer!(tag: "Slow Path", threshold: 5ms, block: {
+///     // your code here
+/// });
 /// ```
 ///
 /// # Examples
@@ -143,37 +423,70 @@
 /// ```
 macro_rules! timer {
     // Times a block with a literal string tag
-    (tag: $tag:literal, block: $block:block) => {{
-        let line = line!();
-        let start = ::std::time::Instant::now();
-        let result = $block;
-        println!(
-            "in {} line {} {}: {} ms",
-            file!(),
-            line,
-            $tag,
-            start.elapsed().as_millis()
-        );
-        result
-    }};
+    (tag: $tag:literal, block: $block:block) => {
+        $crate::timer!(@timed $tag, $block)
+    };
     // Times a block with an identifier tag
-    (tag: $tag:ident, block: $block:block) => {{
-        let line = line!();
-        let start = ::std::time::Instant::now();
-        let result = $block;
-        println!(
-            "in {} line {} {}: {} ms",
-            file!(),
-            line,
-            stringify!($tag),
-            start.elapsed().as_millis()
-        );
+    (tag: $tag:ident, block: $block:block) => {
+        $crate::timer!(@timed stringify!($tag), $block)
+    };
+    // Times a block with a literal string tag, reporting it only if it is slow
+    (tag: $tag:literal, threshold: $threshold:expr, block: $block:block) => {
+        $crate::timer!(@timed $tag, threshold: $threshold, $block)
+    };
+    // Times a block with an identifier tag, reporting it only if it is slow
+    (tag: $tag:ident, threshold: $threshold:expr, block: $block:block) => {
+        $crate::timer!(@timed stringify!($tag), threshold: $threshold, $block)
+    };
+    // Times a block with a tag formatted from arguments, and key-value fields
+    (
+        tag: $tag:literal $(, $arg:expr)*
+        $(; fields: $($key:ident = $value:expr),* $(,)?)?
+        $(; threshold: $threshold:expr)?
+        ; block: $block:block
+    ) => {
+        $crate::timer!(@timed $tag, describe: || (
+            format!($tag $(, $arg)*),
+            vec![$($((stringify!($key), $crate::ToFieldValue::to_field_value(&$value))),*)?],
+        ), $(threshold: $threshold,)? $block)
+    };
+    (
+        @timed $tag:expr,
+        $(describe: $describe:expr,)?
+        $(threshold: $threshold:expr,)?
+        $block:block
+    ) => {{
+        static __CALLSITE: $crate::Callsite =
+            $crate::Callsite::new($tag, file!(), line!(), column!(), module_path!());
+        // Blocks that always `return`, `break` or panic leave the generated
+        // bindings unused or unreachable. The underscores and the `allow`s
+        // only cover the bindings: the one on `_result` is for a lint that
+        // the binding itself causes, and lints in the block still apply.
+        let _span = $crate::SpanGuard::enter(&__CALLSITE)
+            $(.describe($describe))?
+            $(.threshold($crate::timer!(@threshold $threshold)))?;
+        #[allow(clippy::diverging_sub_expression)]
+        let _result = $block;
+        #[allow(unreachable_code)]
+        let result = _span.finish(_result);
         result
     }};
+    // Converts a threshold such as `5ms`, or any `Duration` or `Option<Duration>`
+    (@threshold $threshold:literal) => {{
+        const THRESHOLD: ::std::time::Duration = $crate::duration_literal(stringify!($threshold));
+        THRESHOLD
+    }};
+    (@threshold $threshold:expr) => {
+        $threshold
+    };
     // Times a block with default "Timer" tag
     (block: $block:block) => {
         $crate::timer!(tag: "Timer", block: $block)
     };
+    // Times a block with default "Timer" tag, reporting it only if it is slow
+    (threshold: $threshold:expr, block: $block:block) => {
+        $crate::timer!(tag: "Timer", threshold: $threshold, block: $block)
+    };
     // Times a block with a literal string tag using shorthand syntax
     (#$tag:literal $block:block) => {
         $crate::timer!(tag: $tag, block: $block)
@@ -206,7 +519,8 @@ macro_rules! timer {
 #[cfg(not(any(debug_assertions, feature = "release_also")))]
 /// Times the execution of a code block in debug mode or when `release_also` feature is enabled.
 ///
-/// This macro measures the execution time of a code block and prints the result to stdout.
+/// This macro measures the execution time of a code block and hands the result to the
+/// installed [`Sink`], which prints it to stdout unless [`set_sink`] was called.
 /// In release mode without the `release_also` feature, this macro is disabled and simply
 /// executes the code block without timing.
 ///
@@ -214,6 +528,8 @@ macro_rules! timer {
 ///
 /// ```rust
 /// // Basic usage - times a block of code
+/// use quick_timer::timer;
+///
 /// timer! {
 ///     // your code here
 /// }
@@ -227,6 +543,17 @@ macro_rules! timer {
 /// timer!(tag: "My Tag", block: {
 ///     // your code here
 /// });
+///
+/// // With a formatted tag and key-value fields
+/// let (id, rows) = (7, 120);
+/// timer!(tag: "Request {}", id; fields: rows = rows, cached = false; block: {
+///     // your code here
+/// });
+///
+/// // Only reported if it takes longer than 5 ms
+/// timer!(tag: "Slow Path", threshold: 5ms, block: {
+///     // your code here
+/// });
 /// ```
 ///
 /// # Examples
@@ -255,6 +582,9 @@ macro_rules! timer {
 /// assert_eq!(result, 2);
 /// ```
 macro_rules! timer {
+    (@timed $tag:expr, $block:block) => {
+        $block
+    };
     (tag: $tag:literal, block: { $expr:expr }) => { $expr };
     // Executes a block without timing (literal tag version)
     (tag: $tag:literal, block: $block:block) => {
@@ -265,6 +595,30 @@ macro_rules! timer {
     (tag: $tag:ident, block: $block:block) => {
         $block
     };
+    // Executes a block without timing (threshold versions)
+    (tag: $tag:literal, threshold: $threshold:expr, block: $block:block) => {
+        $block
+    };
+    (tag: $tag:ident, threshold: $threshold:expr, block: $block:block) => {
+        $block
+    };
+    (threshold: $threshold:expr, block: $block:block) => {
+        $block
+    };
+    // Executes a block without timing (formatted tag and fields version)
+    (
+        tag: $tag:literal $(, $arg:expr)*
+        $(; fields: $($key:ident = $value:expr),* $(,)?)?
+        $(; threshold: $threshold:expr)?
+        ; block: $block:block
+    ) => {{
+        // Type-checks the arguments and fields without evaluating them.
+        if false {
+            let _ = format!($tag $(, $arg)*);
+            $($(let _ = $crate::ToFieldValue::to_field_value(&$value);)*)?
+        }
+        $block
+    }};
     (block: { $expr:expr }) => { $expr };
     // Executes a block without timing (default block version)
     (block: $block:block) => {
@@ -304,6 +658,143 @@ macro_rules! timer {
     };
 }
 
+#[macro_export]
+#[cfg(any(debug_assertions, feature = "release_also"))]
+/// Times async code in debug mode or when `release_also` feature is enabled.
+///
+/// This macro accepts the same syntax as [`timer!`], but the block may use `.await`. It
+/// evaluates to a future which, once complete, hands a [`TimingRecord`] to the installed
+/// [`Sink`]. Besides the wall time, the record's [`poll`](TimingRecord::poll) tells how much
+/// of it was spent inside `poll`, how many polls it took and how long the first poll took to
+/// happen. A future dropped before it completes is reported as [`Outcome::Cancelled`]. No
+/// particular async runtime is needed.
+///
+/// Like an `async` block, `return` and `?` inside the block leave the block, not the
+/// enclosing function. In release mode without the `release_also` feature, this macro
+/// evaluates to a plain `async` block.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::timer_async;
+///
+/// async fn load() -> Vec<u8> {
+///     timer_async!(# "load" {
+///         // e.g. `file.read_to_end(&mut buffer).await`
+///         vec![1, 2, 3]
+///     })
+///     .await
+/// }
+///
+/// async fn process() -> usize {
+///     timer_async! {
+///         load().await.len()
+///     }
+///     .await
+/// }
+/// ```
+macro_rules! timer_async {
+    // Times async code with a literal string tag
+    (tag: $tag:literal, block: $block:block) => {
+        $crate::timer_async!(@timed $tag, $block)
+    };
+    // Times async code with an identifier tag
+    (tag: $tag:ident, block: $block:block) => {
+        $crate::timer_async!(@timed stringify!($tag), $block)
+    };
+    (@timed $tag:expr, $block:block) => {{
+        static __CALLSITE: $crate::Callsite =
+            $crate::Callsite::new($tag, file!(), line!(), column!(), module_path!());
+        $crate::instrument(&__CALLSITE, async $block)
+    }};
+    // Times async code with default "Timer" tag
+    (block: $block:block) => {
+        $crate::timer_async!(tag: "Timer", block: $block)
+    };
+    (#$tag:literal $block:block) => {
+        $crate::timer_async!(tag: $tag, block: $block)
+    };
+    (#$tag:ident $block:block) => {
+        $crate::timer_async!(tag: $tag, block: $block)
+    };
+    (#$tag:literal $($tt:tt)*) => {
+        $crate::timer_async!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    (#$tag:ident $($tt:tt)*) => {
+        $crate::timer_async!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    ($block:block) => {
+        $crate::timer_async!(block: $block)
+    };
+    ($($tt:tt)*) => {
+        $crate::timer_async!(block: {
+            $($tt)*
+        })
+    };
+}
+
+#[macro_export]
+#[cfg(not(any(debug_assertions, feature = "release_also")))]
+/// Times async code in debug mode or when `release_also` feature is enabled.
+///
+/// This macro accepts the same syntax as [`timer!`], but the block may use `.await`. It
+/// evaluates to a future which, once complete, hands a [`TimingRecord`] to the installed
+/// [`Sink`]. In release mode without the `release_also` feature, this macro evaluates to a
+/// plain `async` block.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::timer_async;
+///
+/// async fn load() -> Vec<u8> {
+///     timer_async!(# "load" {
+///         vec![1, 2, 3]
+///     })
+///     .await
+/// }
+/// ```
+macro_rules! timer_async {
+    (tag: $tag:tt, block: $block:block) => {
+        async $block
+    };
+    (@timed $tag:expr, $block:block) => {
+        async $block
+    };
+    (block: $block:block) => {
+        async $block
+    };
+    (#$tag:tt $block:block) => {
+        async $block
+    };
+    (#$tag:tt $($tt:tt)*) => {
+        async { $($tt)* }
+    };
+    ($block:block) => {
+        async $block
+    };
+    ($($tt:tt)*) => {
+        async { $($tt)* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+/// The expansion of `#[timed]`: times a function body with `timer!`, or with
+/// `timer_async!` when it starts with `async`.
+macro_rules! __timed {
+    (async $tag:expr, $block:block) => {
+        $crate::timer_async!(@timed $tag, $block)
+    };
+    ($tag:expr, $block:block) => {
+        $crate::timer!(@timed $tag, $block)
+    };
+}
+
 #[macro_export]
 /// Times the execution of a code block and returns both the result and the duration.
 ///
@@ -312,13 +803,28 @@ macro_rules! timer {
 /// `timer_silent!` always performs timing regardless of the build configuration and does not
 /// print anything to stdout.
 ///
+/// When a tag is given, the duration is replaced by a [`TimingRecord`] that also carries
+/// the tag, file, line, column, module and thread of the timer.
+///
 /// # Syntax
 ///
 /// ```rust
 /// // Basic usage - times a block of code
+/// use quick_timer::timer_silent;
+///
 /// let (result, duration) = timer_silent! {
 ///     // your code here
 /// };
+///
+/// // With a tag - returns a `TimingRecord` instead of a `Duration`
+/// let (result, record) = timer_silent!(# "My Tag" {
+///     // your code here
+/// });
+///
+/// // Alternative syntax with tag
+/// let (result, record) = timer_silent!(tag: "My Tag", block: {
+///     // your code here
+/// });
 /// ```
 ///
 /// # Examples
@@ -340,12 +846,65 @@ macro_rules! timer {
 ///     42
 /// };
 /// assert_eq!(result, 42);
+///
+/// // With a tag, the call site is returned as well
+/// let (result, record) = timer_silent! {
+///     ## "Answer"
+///     42
+/// };
+/// assert_eq!(result, 42);
+/// assert_eq!(record.tag, "Answer");
+/// println!("{} took {:?}", record.tag, record.duration);
 /// ```
 macro_rules! timer_silent {
-    (block: $block:block) => {{
-        let start = ::std::time::Instant::now();
+    // Times a block with a literal string tag, returning a `TimingRecord`
+    (tag: $tag:literal, block: $block:block) => {
+        $crate::timer_silent!(@record $tag, $block)
+    };
+    // Times a block with an identifier tag, returning a `TimingRecord`
+    (tag: $tag:ident, block: $block:block) => {
+        $crate::timer_silent!(@record stringify!($tag), $block)
+    };
+    (@record $tag:expr, $block:block) => {{
+        let (result, record) = $crate::timer_silent!(@measure $tag, $block);
+        $crate::aggregate(&record);
+        (result, record)
+    }};
+    (@measure $tag:expr, $block:block) => {{
+        let start = $crate::now();
         let result = $block;
-        (result, start.elapsed())
+        let duration = $crate::now().saturating_duration_since(start);
+        let record = $crate::TimingRecord::new(
+            $tag,
+            file!(),
+            line!(),
+            column!(),
+            module_path!(),
+            start,
+            duration,
+        );
+        (result, record)
+    }};
+    (#$tag:literal $block:block) => {
+        $crate::timer_silent!(tag: $tag, block: $block)
+    };
+    (#$tag:ident $block:block) => {
+        $crate::timer_silent!(tag: $tag, block: $block)
+    };
+    (#$tag:literal $($tt:tt)*) => {
+        $crate::timer_silent!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    (#$tag:ident $($tt:tt)*) => {
+        $crate::timer_silent!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    // Times a block, returning only its `Duration`, without aggregating it
+    (block: $block:block) => {{
+        let (result, record) = $crate::timer_silent!(@measure "Timer", $block);
+        (result, record.duration)
     }};
     ($block:block) => {
         $crate::timer_silent!(block: $block)
@@ -357,6 +916,232 @@ macro_rules! timer_silent {
     };
 }
 
+#[macro_export]
+/// Runs a code block and checks that it took no longer than a budget, returning
+/// `Ok` with the value of the block, or `Err` with a [`BudgetExceeded`].
+///
+/// The budget is written like `250us`, `5ms` or `1.5s`, or given as any
+/// [`Duration`](std::time::Duration). With `runs: N`, the block is run `N` times and
+/// only its fastest run has to fit in the budget, which makes the check less
+/// sensitive to noise; the value of the last run is returned. The block runs in a
+/// closure, so `return` and `?` leave the block rather than the enclosing function.
+/// Unlike `timer!`, this always runs, regardless of the build configuration.
+///
+/// See [`timer_assert!`] for the panicking variant.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::try_timer_assert;
+///
+/// let result = try_timer_assert!(tag: "sum", budget: 1s, runs: 3, {
+///     (0..1000u64).sum::<u64>()
+/// });
+/// assert_eq!(result, Ok(499500));
+/// ```
+macro_rules! try_timer_assert {
+    ($(tag: $tag:tt,)? budget: $budget:expr, $(runs: $runs:expr,)? $block:block) => {
+        $crate::check_budget(
+            $crate::try_timer_assert!(@tag $($tag)?),
+            file!(),
+            line!(),
+            $crate::try_timer_assert!(@budget $budget),
+            $crate::try_timer_assert!(@runs $($runs)?),
+            || $block,
+        )
+    };
+    (@tag) => {
+        "Budget"
+    };
+    (@tag $tag:literal) => {
+        $tag
+    };
+    (@tag $tag:ident) => {
+        stringify!($tag)
+    };
+    // Converts a budget such as `5ms`, or any `Duration`
+    (@budget $budget:literal) => {{
+        const BUDGET: ::std::time::Duration = $crate::duration_literal(stringify!($budget));
+        BUDGET
+    }};
+    (@budget $budget:expr) => {
+        $budget
+    };
+    (@runs) => {
+        1
+    };
+    (@runs $runs:expr) => {
+        $runs
+    };
+}
+
+#[macro_export]
+/// Runs a code block and panics if it took longer than a budget, for performance
+/// tests. Evaluates to the value of the block.
+///
+/// This takes the same options as [`try_timer_assert!`]. The panic message tells
+/// where the assertion is, and how long the block took:
+/// `in FILE line N TAG: X exceeds the budget of Y (best of N runs)`.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::timer_assert;
+///
+/// // In a test
+/// let value = timer_assert!(tag: "parse", budget: 50ms, runs: 5, {
+///     "42".parse::<u32>().unwrap()
+/// });
+/// assert_eq!(value, 42);
+/// ```
+macro_rules! timer_assert {
+    ($($tt:tt)*) => {
+        match $crate::try_timer_assert!($($tt)*) {
+            ::std::result::Result::Ok(result) => result,
+            ::std::result::Result::Err(error) => panic!("{}", error),
+        }
+    };
+}
+
+#[macro_export]
+/// Asserts that a code block runs faster than the given budget, panicking
+/// otherwise. Evaluates to the value of the block.
+///
+/// This is [`timer_assert!`] with the budget first; `tag:` and `runs:` may follow it.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::assert_faster_than;
+///
+/// let sorted = assert_faster_than!(100ms, runs: 3, {
+///     let mut values = vec![3, 1, 2];
+///     values.sort();
+///     values
+/// });
+/// assert_eq!(sorted, [1, 2, 3]);
+/// ```
+macro_rules! assert_faster_than {
+    ($budget:expr, $(tag: $tag:tt,)? $(runs: $runs:expr,)? $block:block) => {
+        $crate::timer_assert!($(tag: $tag,)? budget: $budget, $(runs: $runs,)? $block)
+    };
+}
+
+#[macro_export]
+/// Benchmarks a code block, returning its [`BenchStats`].
+///
+/// The block is run repeatedly with the default [`Bench`] settings: a warmup, then
+/// samples of as many runs as fit in the measurement time, so that even code taking
+/// nanoseconds can be measured. The value of the block is passed through [`black_box`];
+/// pass its inputs through [`black_box`] too, so that the compiler cannot precompute it.
+/// Unlike `timer!`, `bench!` always runs, regardless of the build configuration.
+///
+/// Use [`Bench`] directly to change the warmup, measurement time or number of samples.
+/// Nothing is printed; the statistics display as a summary line.
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{bench, black_box};
+///
+/// let stats = bench!(# "sum" {
+///     (0..black_box(1000u64)).sum::<u64>()
+/// });
+/// // e.g. `sum: 312 ns ± 4.00 ns (median 311 ns, ...; 50 samples of 32051 runs)`
+/// println!("{}", stats);
+/// assert!(stats.min <= stats.median);
+/// ```
+macro_rules! bench {
+    // Benchmarks a block with a literal string tag
+    (tag: $tag:literal, block: $block:block) => {
+        $crate::bench!(@run $tag, $block)
+    };
+    // Benchmarks a block with an identifier tag
+    (tag: $tag:ident, block: $block:block) => {
+        $crate::bench!(@run stringify!($tag), $block)
+    };
+    (@run $tag:expr, $block:block) => {
+        $crate::Bench::new().tag($tag).run(|| $block)
+    };
+    // Benchmarks a block with the default "Bench" tag
+    (block: $block:block) => {
+        $crate::bench!(tag: "Bench", block: $block)
+    };
+    (#$tag:literal $block:block) => {
+        $crate::bench!(tag: $tag, block: $block)
+    };
+    (#$tag:ident $block:block) => {
+        $crate::bench!(tag: $tag, block: $block)
+    };
+    (#$tag:literal $($tt:tt)*) => {
+        $crate::bench!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    (#$tag:ident $($tt:tt)*) => {
+        $crate::bench!(tag: $tag, block: {
+            $($tt)*
+        })
+    };
+    ($block:block) => {
+        $crate::bench!(block: $block)
+    };
+    ($($tt:tt)*) => {
+        $crate::bench!(block: {
+            $($tt)*
+        })
+    };
+}
+
+#[macro_export]
+/// Benchmarks several variants of a code block against each other, returning a
+/// [`Comparison`].
+///
+/// Each variant is tagged with a string literal. Their samples are taken in turns, so that
+/// drift in the speed of the machine affects every variant alike, and every variant is
+/// compared with the first one: how many times faster it is, with a 95% confidence
+/// interval, and whether the difference is significant according to a Mann-Whitney U test.
+/// See [`Bench::compare`] for details. Nothing is printed; the comparison displays as the
+/// statistics of every variant followed by how each compares with the first.
+///
+/// At least two variants are needed; a single one does not compile:
+///
+/// ```compile_fail
+/// let comparison = quick_timer::compare! { "alone" => { 1 + 1 } };
+/// ```
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{black_box, compare};
+///
+/// let data: Vec<u32> = (0..1000).collect();
+/// let comparison = compare! {
+///     "linear" => { data.iter().position(|&x| x == black_box(700)) },
+///     "binary" => { data.binary_search(&black_box(700)) },
+/// };
+/// // The statistics of both, then e.g.
+/// // `binary is 41.20x faster than linear [40.85x, 41.57x], p = 0.000`
+/// println!("{}", comparison);
+/// assert_eq!(comparison.relative[0].verdict, quick_timer::Verdict::Faster);
+/// ```
+macro_rules! compare {
+    ($first_tag:literal => $first_block:block, $($tag:literal => $block:block),+ $(,)?) => {
+        $crate::Bench::new().compare(::std::vec![
+            $crate::compare!(@variant $first_tag, $first_block),
+            $($crate::compare!(@variant $tag, $block)),+
+        ])
+    };
+    (@variant $tag:literal, $block:block) => {
+        (
+            $tag,
+            ::std::boxed::Box::new(|| {
+                $crate::black_box($block);
+            }) as ::std::boxed::Box<dyn ::std::ops::FnMut() + '_>,
+        )
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,4 +1166,30 @@ mod tests {
             std::any::TypeId::of::<std::time::Duration>()
         );
     }
+
+    #[test]
+    fn test_timer_silent_record() {
+        let (result, record) = timer_silent!(# Tagged {
+            1 + 1
+        });
+        assert_eq!(result, 2);
+        assert_eq!(record.tag, "Tagged");
+        assert_eq!(record.file, file!());
+        assert_eq!(record.line, line!() - 6);
+        assert_eq!(record.module, module_path!());
+        assert_eq!(record.thread.id(), std::thread::current().id());
+    }
+
+    #[test]
+    fn test_timer_silent_mock_clock() {
+        let clock = MockClock::new();
+        let (result, record) = with_clock(clock.clone(), || {
+            timer_silent!(# Mocked {
+                clock.advance(std::time::Duration::from_micros(1500));
+                "done"
+            })
+        });
+        assert_eq!(result, "done");
+        assert_eq!(record.duration, std::time::Duration::from_micros(1500));
+    }
 }
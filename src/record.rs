@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! The structured record produced by every timing.
+
+use crate::{FieldValue, HumanDuration, PollStats};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::thread::Thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A single timing measurement, together with the call site that produced it.
+///
+/// Records are handed to the installed [`Sink`](crate::Sink) by `timer!`, and
+/// returned by the tagged forms of `timer_silent!`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TimingRecord {
+    /// The tag given to the timer, or `"Timer"` when none was given.
+    ///
+    /// For tags with format arguments, this is the format string, so that
+    /// every timing of a call site shares its tag; see
+    /// [`label`](TimingRecord::label) for the formatted tag.
+    pub tag: &'static str,
+    /// The tag formatted with its arguments, for timers whose tag has them.
+    pub label: Option<String>,
+    /// The key-value fields given to the timer, in the order they were given.
+    pub fields: Vec<(&'static str, FieldValue)>,
+    /// The source file of the timer, as reported by `file!()`.
+    pub file: &'static str,
+    /// The source line of the timer, as reported by `line!()`.
+    pub line: u32,
+    /// The source column of the timer, as reported by `column!()`.
+    pub column: u32,
+    /// The module path of the timer, as reported by `module_path!()`.
+    pub module: &'static str,
+    /// When the timed block started, relative to the first time this process
+    /// read the [clock](crate::now).
+    pub start: Duration,
+    /// How long the timed block took.
+    pub duration: Duration,
+    /// The thread the timed block ran on.
+    pub thread: Thread,
+    /// How many `timer!` blocks enclosed this one on its thread; zero for
+    /// an outermost block.
+    pub depth: usize,
+    /// The part of `duration` not spent in nested `timer!` blocks.
+    pub self_time: Duration,
+    /// How the future was polled, for timings of futures.
+    pub poll: Option<PollStats>,
+    /// How the timed block was left.
+    pub outcome: Outcome,
+    /// The threshold of the timer, if it has one: timings no slower than it
+    /// are aggregated into the statistics, but not reported to the sink.
+    pub threshold: Option<Duration>,
+}
+
+/// How a timed block was left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The block ran to its end.
+    Completed,
+    /// The block was left early, through `?`, `return` or `break`.
+    EarlyExit,
+    /// The block panicked.
+    Panicked,
+    /// The future of an async block was dropped before it completed.
+    Cancelled,
+}
+
+impl TimingRecord {
+    /// The formatted tag if there is one, and the tag otherwise.
+    pub fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or(self.tag)
+    }
+
+    /// The wall-clock time at which the timed block started.
+    ///
+    /// This is derived from [`start`](TimingRecord::start), so it is only as
+    /// accurate as the system clock was when the first timing was made.
+    pub fn start_time(&self) -> SystemTime {
+        epoch_pair().1 + self.start
+    }
+
+    /// Whether the timing was slower than the
+    /// [threshold](TimingRecord::threshold) of its timer, and so is reported
+    /// as a warning.
+    pub fn is_slow(&self) -> bool {
+        self.threshold
+            .map_or(false, |threshold| self.duration > threshold)
+    }
+
+    /// Whether the timing is hidden from the sink by its threshold.
+    pub(crate) fn is_below_threshold(&self) -> bool {
+        self.threshold.is_some() && !self.is_slow()
+    }
+
+    #[doc(hidden)]
+    pub fn new(
+        tag: &'static str,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        module: &'static str,
+        start: Instant,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            tag,
+            file,
+            line,
+            column,
+            module,
+            label: None,
+            fields: Vec::new(),
+            start: start.saturating_duration_since(epoch()),
+            duration,
+            thread: std::thread::current(),
+            depth: 0,
+            self_time: duration,
+            poll: None,
+            outcome: Outcome::Completed,
+            threshold: crate::threshold(),
+        }
+    }
+}
+
+/// The instant that record start offsets are measured from, and the wall-clock
+/// time it corresponds to.
+static EPOCH: RwLock<Option<(Instant, SystemTime)>> = RwLock::new(None);
+/// Set once the epoch is fixed, so that reading the clock stays cheap.
+static EPOCH_SET: AtomicBool = AtomicBool::new(false);
+
+/// Fixes the epoch at `now` if it is not fixed yet. The clock calls this every
+/// time it is read, so that every timing starts after the epoch.
+#[inline]
+pub(crate) fn fix_epoch(now: Instant) {
+    if EPOCH_SET.load(Ordering::Acquire) {
+        return;
+    }
+    EPOCH
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get_or_insert_with(|| (now, SystemTime::now()));
+    EPOCH_SET.store(true, Ordering::Release);
+}
+
+/// Returns the instant that record start offsets are measured from.
+pub(crate) fn epoch() -> Instant {
+    epoch_pair().0
+}
+
+fn epoch_pair() -> (Instant, SystemTime) {
+    if !EPOCH_SET.load(Ordering::Acquire) {
+        // Reading the clock fixes the epoch.
+        crate::clock::now();
+    }
+    EPOCH
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .expect("the epoch is fixed by reading the clock")
+}
+
+/// Formats the record the way `timer!` prints it: `in FILE line N TAG: X`,
+/// where the duration is shown as a [`HumanDuration`], e.g. `1.23 ms`.
+/// The tag is formatted with its arguments, and followed by the fields if
+/// there are any: `in FILE line N TAG [KEY=VALUE, ...]: X`.
+///
+/// Timings slower than the threshold of their timer are shown as warnings:
+/// `warning: in FILE line N TAG: X (over T)`.
+///
+/// Nested records are indented by their depth, and records with nested
+/// timers also show their self time: `in FILE line N TAG: X (self Y)`.
+/// Timings of futures show their busy time and poll count:
+/// `in FILE line N TAG: X (busy Y, N polls)`. Blocks that did not run
+/// to their end are marked `(early exit)`, `(panicked)` or `(cancelled)`.
+impl fmt::Display for TimingRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:indent$}{}in {} line {} {}",
+            "",
+            if self.is_slow() { "warning: " } else { "" },
+            self.file,
+            self.line,
+            self.name(),
+            indent = self.depth * 2
+        )?;
+        for (index, (key, value)) in self.fields.iter().enumerate() {
+            let open = if index == 0 { " [" } else { ", " };
+            write!(f, "{}{}={}", open, key, value)?;
+        }
+        if !self.fields.is_empty() {
+            write!(f, "]")?;
+        }
+        write!(f, ": {}", HumanDuration::new(self.duration))?;
+        if self.self_time != self.duration {
+            write!(f, " (self {})", HumanDuration::new(self.self_time))?;
+        }
+        if let Some(poll) = &self.poll {
+            write!(
+                f,
+                " (busy {}, {} polls)",
+                HumanDuration::new(poll.busy),
+                poll.polls
+            )?;
+        }
+        match self.outcome {
+            Outcome::Completed => {}
+            Outcome::EarlyExit => write!(f, " (early exit)")?,
+            Outcome::Panicked => write!(f, " (panicked)")?,
+            Outcome::Cancelled => write!(f, " (cancelled)")?,
+        }
+        if let (true, Some(threshold)) = (self.is_slow(), self.threshold) {
+            write!(f, " (over {})", HumanDuration::new(threshold))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Benchmarking over input sizes, and estimating how the time grows.
+
+use crate::bench::{black_box, Bench, BenchStats};
+use crate::HumanDuration;
+use std::fmt;
+use std::time::Duration;
+
+/// A candidate growth rate of the time taken, in terms of the input size `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Complexity {
+    /// `O(1)`
+    Constant,
+    /// `O(log n)`
+    Logarithmic,
+    /// `O(n)`
+    Linear,
+    /// `O(n log n)`
+    Linearithmic,
+    /// `O(n²)`
+    Quadratic,
+    /// `O(n³)`
+    Cubic,
+}
+
+impl Complexity {
+    /// Every candidate, from slowest-growing to fastest-growing.
+    pub const ALL: [Complexity; 6] = [
+        Complexity::Constant,
+        Complexity::Logarithmic,
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+        Complexity::Cubic,
+    ];
+
+    /// The growth function at `n`, e.g. `n * log2(n)` for
+    /// [`Linearithmic`](Complexity::Linearithmic).
+    pub fn eval(self, n: f64) -> f64 {
+        match self {
+            Complexity::Constant => 1.0,
+            Complexity::Logarithmic => n.log2().max(0.0),
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.log2().max(0.0),
+            Complexity::Quadratic => n * n,
+            Complexity::Cubic => n * n * n,
+        }
+    }
+
+    fn term(self) -> &'static str {
+        match self {
+            Complexity::Constant => "1",
+            Complexity::Logarithmic => "log n",
+            Complexity::Linear => "n",
+            Complexity::Linearithmic => "n log n",
+            Complexity::Quadratic => "n²",
+            Complexity::Cubic => "n³",
+        }
+    }
+}
+
+/// Formats as big O notation, e.g. `O(n log n)`.
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("O({})", self.term()))
+    }
+}
+
+/// How well the times fit one [`Complexity`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct Fit {
+    /// The candidate growth rate.
+    pub complexity: Complexity,
+    /// The time per unit of the growth function, in nanoseconds: the fitted
+    /// time at size `n` is `coefficient * complexity.eval(n)`.
+    pub coefficient: f64,
+    /// The root mean square of the residuals, relative to the mean time.
+    /// Lower is a better fit.
+    pub rms: f64,
+}
+
+impl Fit {
+    /// Fits `time = coefficient * complexity(n)` to `(n, time)` points by least
+    /// squares.
+    fn new(complexity: Complexity, points: &[(f64, f64)]) -> Self {
+        let (mut sum_gt, mut sum_gg) = (0.0, 0.0);
+        for &(n, time) in points {
+            let g = complexity.eval(n);
+            sum_gt += g * time;
+            sum_gg += g * g;
+        }
+        let coefficient = if sum_gg > 0.0 { sum_gt / sum_gg } else { 0.0 };
+
+        let count = points.len().max(1) as f64;
+        let mean = points.iter().map(|p| p.1).sum::<f64>() / count;
+        let squares: f64 = points
+            .iter()
+            .map(|&(n, time)| (time - coefficient * complexity.eval(n)).powi(2))
+            .sum();
+        let rms = (squares / count).sqrt();
+        Self {
+            complexity,
+            coefficient,
+            rms: if mean > 0.0 { rms / mean } else { rms },
+        }
+    }
+
+    /// The fitted time at size `n`.
+    pub fn predict(&self, n: usize) -> Duration {
+        let nanos = self.coefficient * self.complexity.eval(n as f64);
+        Duration::from_nanos(nanos.max(0.0).round() as u64)
+    }
+}
+
+/// Formats as e.g. `O(n log n) ≈ 2.31 ns × n log n (RMS 3.1%)`.
+impl fmt::Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ≈ {:.3} ns × {} (RMS {:.1}%)",
+            self.complexity,
+            self.coefficient,
+            self.complexity.term(),
+            self.rms * 100.0
+        )
+    }
+}
+
+/// The results of [`Bench::scaling`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Scaling {
+    /// The statistics at each input size, in the order the sizes were given.
+    pub points: Vec<(usize, BenchStats)>,
+    /// The fit of every candidate [`Complexity`], best first.
+    pub fits: Vec<Fit>,
+}
+
+impl Scaling {
+    /// The complexity that fits the median times best.
+    pub fn best(&self) -> Fit {
+        self.fits[0]
+    }
+}
+
+/// Prints the median time at each size, then the best fit.
+impl fmt::Display for Scaling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .points
+            .iter()
+            .map(|(n, _)| n.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (n, stats) in &self.points {
+            writeln!(
+                f,
+                "n = {:>width$}: {:>10} ± {}",
+                n,
+                HumanDuration::new(stats.median).to_string(),
+                HumanDuration::new(stats.mad),
+                width = width
+            )?;
+        }
+        write!(f, "best fit: {}", self.best())
+    }
+}
+
+impl Bench {
+    /// Benchmarks `f` at every input size in `sizes`, and estimates how its
+    /// time grows with the size.
+    ///
+    /// For each size `n`, the input is made once by `setup(n)` and `f` is
+    /// benchmarked on it with these settings, so the whole run takes about
+    /// `sizes.len()` times the warmup and measurement time. The median times
+    /// are then fitted to every [`Complexity`] by least squares.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sizes` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use quick_timer::{Bench, Complexity};
+    ///
+    /// let scaling = Bench::new().scaling(
+    ///     [1_000, 2_000, 4_000, 8_000, 16_000],
+    ///     |n| (0..n as u64).rev().collect::<Vec<_>>(),
+    ///     |input| {
+    ///         let mut input = input.clone();
+    ///         input.sort_unstable();
+    ///         input
+    ///     },
+    /// );
+    /// println!("{}", scaling);
+    /// assert_eq!(scaling.best().complexity, Complexity::Linearithmic);
+    /// ```
+    pub fn scaling<I, T>(
+        &self,
+        sizes: impl IntoIterator<Item = usize>,
+        mut setup: impl FnMut(usize) -> I,
+        mut f: impl FnMut(&I) -> T,
+    ) -> Scaling {
+        let points: Vec<(usize, BenchStats)> = sizes
+            .into_iter()
+            .map(|n| {
+                let input = setup(n);
+                let stats = self.run(|| f(black_box(&input)));
+                (n, stats)
+            })
+            .collect();
+        assert!(!points.is_empty(), "at least one input size is needed");
+
+        let times: Vec<(f64, f64)> = points
+            .iter()
+            .map(|(n, stats)| (*n as f64, stats.median.as_nanos() as f64))
+            .collect();
+        let mut fits: Vec<Fit> = Complexity::ALL
+            .iter()
+            .map(|&complexity| Fit::new(complexity, &times))
+            .collect();
+        // Stable, so that ties go to the slower-growing complexity.
+        fits.sort_by(|a, b| a.rms.total_cmp(&b.rms));
+        Scaling { points, fits }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{with_clock, MockClock};
+
+    fn scaling(cost: impl Fn(f64) -> f64) -> Scaling {
+        let clock = MockClock::new();
+        let bench = Bench::new()
+            .warmup(Duration::from_micros(100))
+            .measurement_time(Duration::from_millis(1))
+            .samples(5);
+        with_clock(clock.clone(), || {
+            bench.scaling(
+                [64, 128, 256, 512, 1024, 2048, 4096],
+                |n| n as f64,
+                |&n| clock.advance(Duration::from_nanos(cost(n) as u64)),
+            )
+        })
+    }
+
+    #[test]
+    fn test_best_fit() {
+        let best = scaling(|n| 5.0 * n * n.log2()).best();
+        assert_eq!(best.complexity, Complexity::Linearithmic);
+        assert!((best.coefficient - 5.0).abs() < 1e-9);
+        assert!(best.rms < 1e-9);
+        assert_eq!(best.predict(1024), Duration::from_nanos(51_200));
+
+        let linear = scaling(|n| 3.0 * n).best();
+        assert_eq!(linear.complexity, Complexity::Linear);
+        assert_eq!(scaling(|n| n * n).best().complexity, Complexity::Quadratic);
+        assert_eq!(scaling(|_| 40.0).best().complexity, Complexity::Constant);
+        assert_eq!(linear.to_string(), "O(n) ≈ 3.000 ns × n (RMS 0.0%)");
+    }
+}
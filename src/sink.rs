@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Output sinks that receive the records produced by `timer!`.
+
+use crate::TimingRecord;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// A destination for timing records.
+///
+/// Install one with [`set_sink`]. Until a sink is installed, `timer!` behaves
+/// as if [`StdoutSink`] was installed.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{set_sink, timer, Sink, TimingRecord};
+///
+/// struct Log;
+///
+/// impl Sink for Log {
+///     fn record(&self, record: &TimingRecord) {
+///         eprintln!("[timing] {} took {:?}", record.tag, record.duration);
+///     }
+/// }
+///
+/// set_sink(Log);
+/// timer!(# "work" {
+///     println!("Doing some work");
+/// });
+/// ```
+pub trait Sink: Send + Sync {
+    /// Handles a single timing record.
+    fn record(&self, record: &TimingRecord);
+
+    /// Flushes any buffered output. The default implementation does nothing.
+    fn flush(&self) {}
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn record(&self, record: &TimingRecord) {
+        (**self).record(record)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for Arc<S> {
+    fn record(&self, record: &TimingRecord) {
+        (**self).record(record)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+/// Prints every record to stdout. This is the default sink.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn record(&self, record: &TimingRecord) {
+        println!("{}", record);
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// Prints every record to stderr.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+    fn record(&self, record: &TimingRecord) {
+        eprintln!("{}", record);
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Discards every record.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSink;
+
+impl Sink for NullSink {
+    fn record(&self, _record: &TimingRecord) {}
+}
+
+static SINK: RwLock<Option<Arc<dyn Sink>>> = RwLock::new(None);
+
+/// Installs `sink` as the global destination for timing records, replacing
+/// the previously installed one.
+pub fn set_sink<S: Sink + 'static>(sink: S) {
+    let previous = write_sink().replace(Arc::new(sink));
+    if let Some(previous) = previous {
+        previous.flush();
+    }
+}
+
+/// Flushes the installed sink.
+///
+/// [`ChromeTraceSink`](crate::ChromeTraceSink),
+/// [`FoldedStackSink`](crate::FoldedStackSink) and
+/// [`SpeedscopeSink`](crate::SpeedscopeSink) keep what they collect in memory,
+/// and rewrite their whole file whenever they are flushed: by this function
+/// or a [`FlushGuard`], when [`set_sink`] replaces them, and when the process
+/// exits normally on Unix and Windows. A process that aborts, is killed, or
+/// runs on another platform needs this to be called, or a [`FlushGuard`] to be
+/// dropped, before `main` returns. Failures to write the file are printed to
+/// stderr.
+pub fn flush() {
+    if let Some(sink) = current_sink() {
+        sink.flush();
+    }
+}
+
+/// Flushes the installed sink when dropped.
+///
+/// Keep one alive for the whole of `main`, so that the sink is flushed however
+/// `main` returns, including by `?` or a panic.
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{set_sink, ChromeTraceSink, FlushGuard};
+///
+/// fn main() -> std::io::Result<()> {
+///     set_sink(ChromeTraceSink::new("trace.json"));
+///     let _flush = FlushGuard::new();
+///     std::fs::read("input.txt")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+#[must_use = "the sink is flushed when the guard is dropped"]
+pub struct FlushGuard {
+    _private: (),
+}
+
+impl FlushGuard {
+    /// Creates a guard that flushes the installed sink when dropped.
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
+/// The file that a sink keeping its output in memory writes when flushed, as
+/// described in [`flush`].
+#[derive(Debug)]
+pub(crate) struct OutputFile {
+    path: PathBuf,
+    /// What the file holds, for error messages.
+    what: &'static str,
+}
+
+impl OutputFile {
+    /// Creates the output file of a sink, making sure the installed sink is
+    /// flushed when the process exits.
+    pub(crate) fn new(path: &Path, what: &'static str) -> Self {
+        flush_at_exit();
+        Self {
+            path: path.to_path_buf(),
+            what,
+        }
+    }
+
+    /// Replaces the contents of the file with what `write` writes, printing
+    /// any failure to stderr.
+    pub(crate) fn write<F>(&self, write: F)
+    where
+        F: FnOnce(BufWriter<File>) -> io::Result<()>,
+    {
+        let result = File::create(&self.path).and_then(|file| write(BufWriter::new(file)));
+        if let Err(error) = result {
+            eprintln!(
+                "quick-timer: failed to write {} to {}: {}",
+                self.what,
+                self.path.display(),
+                error
+            );
+        }
+    }
+}
+
+/// Makes sure the installed sink is flushed when the process exits. Does
+/// nothing on platforms without a C `atexit`.
+fn flush_at_exit() {
+    #[cfg(any(unix, windows))]
+    {
+        extern "C" {
+            fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+        }
+
+        extern "C" fn flush_installed_sink() {
+            // Unwinding out of an `atexit` handler would abort the process.
+            let _ = std::panic::catch_unwind(flush);
+        }
+
+        static REGISTER: std::sync::Once = std::sync::Once::new();
+        REGISTER.call_once(|| {
+            // SAFETY: the C runtime of every Unix and Windows target provides
+            // `atexit`, and the handler neither unwinds nor takes arguments.
+            unsafe {
+                atexit(flush_installed_sink);
+            }
+        });
+    }
+}
+
+/// Adds `record` to the aggregated statistics, then hands it to the installed
+/// sink, or prints it to stdout if none is installed. Records no slower than
+/// their [threshold](crate::set_threshold) are only aggregated.
+pub fn dispatch(record: &TimingRecord) {
+    crate::stats::aggregate(record);
+    if !record.is_below_threshold() {
+        send(record);
+    }
+}
+
+/// Hands `record` to the installed sink, or prints it to stdout if none is
+/// installed, without aggregating it.
+pub(crate) fn send(record: &TimingRecord) {
+    match current_sink() {
+        Some(sink) => sink.record(record),
+        None => StdoutSink.record(record),
+    }
+}
+
+fn current_sink() -> Option<Arc<dyn Sink>> {
+    // The sink is cloned out so that it is not called with the lock held;
+    // a sink that itself uses `timer!` would otherwise deadlock.
+    SINK.read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+fn write_sink() -> std::sync::RwLockWriteGuard<'static, Option<Arc<dyn Sink>>> {
+    SINK.write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+    use std::thread::{self, ThreadId};
+
+    /// Held by the tests that install a sink or change the default threshold.
+    static INSTALLED: Mutex<()> = Mutex::new(());
+
+    /// Waits until no other test installs a sink or changes the default
+    /// threshold, for tests that depend on neither changing.
+    pub(crate) fn lock() -> MutexGuard<'static, ()> {
+        INSTALLED
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Changes the default threshold until dropped, even by a panic. Only
+    /// use it inside [`capture`], which keeps other tests from seeing it.
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    pub(crate) struct DefaultThreshold(Option<std::time::Duration>);
+
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    impl DefaultThreshold {
+        pub(crate) fn set(threshold: Option<std::time::Duration>) -> Self {
+            let previous = crate::threshold();
+            crate::set_threshold(threshold);
+            Self(previous)
+        }
+    }
+
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    impl Drop for DefaultThreshold {
+        fn drop(&mut self) {
+            crate::set_threshold(self.0);
+        }
+    }
+
+    struct Capture {
+        thread: ThreadId,
+        records: Mutex<Vec<TimingRecord>>,
+    }
+
+    impl Sink for Capture {
+        fn record(&self, record: &TimingRecord) {
+            if record.thread.id() == self.thread {
+                self.records.lock().unwrap().push(record.clone());
+            }
+        }
+    }
+
+    /// Runs `f` with a capturing sink installed, returning the records that
+    /// were dispatched from the current thread.
+    pub(crate) fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<TimingRecord>) {
+        let _installed = lock();
+        let sink = Arc::new(Capture {
+            thread: thread::current().id(),
+            records: Mutex::new(Vec::new()),
+        });
+        set_sink(sink.clone());
+        let result = f();
+        set_sink(StdoutSink);
+        let records = std::mem::take(&mut *sink.records.lock().unwrap());
+        (result, records)
+    }
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "release_also")))]
+mod tests {
+    use super::testing::capture;
+
+    #[test]
+    fn test_timer_dispatches_to_sink() {
+        let (result, records) = capture(|| crate::timer!(# "sink test" { 1 + 1 }));
+
+        assert_eq!(result, 2);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tag, "sink test");
+        assert_eq!(records[0].file, file!());
+        assert_eq!(records[0].module, module_path!());
+    }
+}
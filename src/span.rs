@@ -0,0 +1,723 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! The per-thread stack of open `timer!` blocks, used to nest timings.
+
+use crate::active::{self, ActiveSpan};
+use crate::{clock, Callsite, FieldValue, Outcome, TimingRecord};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A `timer!` block that has been entered but not yet left.
+struct Frame {
+    id: u64,
+    /// Time spent in nested blocks that have already finished.
+    child_time: Duration,
+    /// Whether a nested block was reported to the sink, in which case this
+    /// one is reported too, whatever its threshold.
+    nested_reported: bool,
+    /// Finished nested records to report, in tree order.
+    records: Vec<TimingRecord>,
+}
+
+/// The most records a thread holds back until its outermost span finishes.
+/// Past that, the held records are reported, and spans are reported as they
+/// close until the outermost span finishes.
+const MAX_BUFFERED: usize = 4096;
+
+/// The open spans of one thread, outermost first.
+struct Stack {
+    frames: Vec<Frame>,
+    /// How many records the frames hold in all.
+    buffered: usize,
+    /// Whether `MAX_BUFFERED` was exceeded since the outermost span opened.
+    streaming: bool,
+}
+
+impl Stack {
+    /// Removes the frames of spans that were closed on another thread,
+    /// returning the records held by an outermost one, which are now due.
+    fn prune(&mut self) -> Vec<TimingRecord> {
+        let mut due = Vec::new();
+        if !HAS_ORPHANS.load(Ordering::Relaxed) {
+            return due;
+        }
+        let mut orphans = orphans();
+        let mut index = 0;
+        while index < self.frames.len() {
+            let id = self.frames[index].id;
+            let position = match orphans.iter().position(|&orphan| orphan == id) {
+                Some(position) => position,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            };
+            orphans.swap_remove(position);
+            let mut records = self.frames.remove(index).records;
+            // The spans nested in the orphan move up a level.
+            let nested = self.frames[index..]
+                .iter_mut()
+                .flat_map(|frame| frame.records.iter_mut());
+            for record in records.iter_mut().chain(nested) {
+                record.depth -= 1;
+            }
+            match index.checked_sub(1) {
+                Some(parent) => self.frames[parent].records.append(&mut records),
+                None => {
+                    self.buffered -= records.len();
+                    due.append(&mut records);
+                }
+            }
+        }
+        HAS_ORPHANS.store(!orphans.is_empty(), Ordering::Relaxed);
+        if self.frames.is_empty() {
+            self.streaming = false;
+        }
+        due
+    }
+
+    /// Takes every held record, outermost frame first.
+    fn drain(&mut self) -> Vec<TimingRecord> {
+        self.buffered = 0;
+        self.frames
+            .iter_mut()
+            .flat_map(|frame| frame.records.drain(..))
+            .collect()
+    }
+}
+
+impl Drop for Stack {
+    /// Reports the records held for spans of an exiting thread that were
+    /// closed elsewhere.
+    fn drop(&mut self) {
+        for record in &self.prune() {
+            crate::sink::send(record);
+        }
+    }
+}
+
+thread_local! {
+    static STACK: RefCell<Stack> = const {
+        RefCell::new(Stack {
+            frames: Vec::new(),
+            buffered: 0,
+            streaming: false,
+        })
+    };
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The ids of spans that were closed on another thread than the one that
+/// opened them, whose frames are still on the stack of the opening thread.
+/// That thread removes them the next time it opens or closes a span.
+static ORPHANS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// Whether `ORPHANS` may be non-empty, so that pruning is usually free.
+static HAS_ORPHANS: AtomicBool = AtomicBool::new(false);
+
+fn orphans() -> MutexGuard<'static, Vec<u64>> {
+    ORPHANS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Returns a new span id, unique in the process.
+pub(crate) fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Marks a `timer!` block as open on the current thread, so that timers
+/// nested inside it become its children.
+///
+/// The timing is reported when the guard is dropped, so a block left through
+/// `?`, `return`, `break` or a panic is reported too, with the matching
+/// [`Outcome`]. Spans are aggregated as they close, but records of nested
+/// timers are held back until the outermost block on the thread finishes,
+/// and then dispatched together, parents before children. A thread holds at
+/// most `MAX_BUFFERED` records back; past that, they are dispatched as their
+/// spans close until the outermost block finishes.
+///
+/// The guard is `Send`, so that a future holding a `timer!` block across an
+/// `.await` can move between threads. A span closed on another thread than
+/// the one that opened it is reported at once, at depth 0, with all of its
+/// time as self time.
+#[doc(hidden)]
+#[must_use]
+pub struct SpanGuard {
+    id: u64,
+    tag: &'static str,
+    file: &'static str,
+    line: u32,
+    column: u32,
+    module: &'static str,
+    /// The formatted tag, for tags with format arguments.
+    label: Option<String>,
+    fields: Vec<(&'static str, FieldValue)>,
+    /// The threshold given to the timer, overriding the default one.
+    threshold: Option<Option<Duration>>,
+    /// `None` if the filter disabled the timer, which then reports nothing.
+    start: Option<Instant>,
+    /// Whether the span is in the registry of [active](crate::active) spans.
+    tracked: bool,
+    /// The outcome to report, once it is known.
+    outcome: Option<Outcome>,
+    /// The outcome to report if the guard is dropped without one.
+    unfinished: Outcome,
+}
+
+impl SpanGuard {
+    /// Opens a span on the current thread and starts timing it, unless the
+    /// [filter](crate::set_filter) disables `callsite`.
+    #[inline]
+    pub fn enter(callsite: &'static Callsite) -> Self {
+        let guard = Self::new(
+            callsite.tag,
+            callsite.file,
+            callsite.line,
+            callsite.column,
+            callsite.module,
+        );
+        if callsite.is_enabled() {
+            guard.open()
+        } else {
+            guard
+        }
+    }
+
+    /// Like [`enter`](SpanGuard::enter), for spans without a static call
+    /// site, which consult the filter every time.
+    pub(crate) fn enter_uncached(
+        tag: &'static str,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        module: &'static str,
+    ) -> Self {
+        let guard = Self::new(tag, file, line, column, module);
+        if crate::filter::enabled(tag, module) {
+            guard.open()
+        } else {
+            guard
+        }
+    }
+
+    /// Creates a disabled guard, which reports nothing.
+    fn new(
+        tag: &'static str,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        module: &'static str,
+    ) -> Self {
+        Self {
+            id: 0,
+            tag,
+            file,
+            line,
+            column,
+            module,
+            label: None,
+            fields: Vec::new(),
+            threshold: None,
+            start: None,
+            tracked: false,
+            outcome: None,
+            unfinished: Outcome::EarlyExit,
+        }
+    }
+
+    /// Pushes the span on the stack of the current thread and starts timing.
+    fn open(mut self) -> Self {
+        self.id = next_id();
+        let due = STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let due = stack.prune();
+            stack.frames.push(Frame {
+                id: self.id,
+                child_time: Duration::ZERO,
+                nested_reported: false,
+                records: Vec::new(),
+            });
+            due
+        });
+        for record in &due {
+            crate::sink::send(record);
+        }
+        let start = clock::now();
+        self.tracked = active::is_tracking();
+        if self.tracked {
+            active::enter(ActiveSpan {
+                id: self.id,
+                tag: self.tag,
+                label: None,
+                file: self.file,
+                line: self.line,
+                column: self.column,
+                module: self.module,
+                start,
+            });
+        }
+        self.start = Some(start);
+        self
+    }
+
+    /// Sets the formatted tag and the fields of the span from `describe`,
+    /// which is only called if the span is enabled. A label equal to the tag
+    /// is dropped.
+    pub fn describe<F>(mut self, describe: F) -> Self
+    where
+        F: FnOnce() -> (String, Vec<(&'static str, FieldValue)>),
+    {
+        if self.start.is_some() {
+            let (label, fields) = describe();
+            if label != self.tag {
+                self.label = Some(label);
+            }
+            self.fields = fields;
+            // Formatting is not part of the timed block.
+            let start = clock::now();
+            if self.tracked {
+                active::describe(self.id, self.label.clone(), start);
+            }
+            self.start = Some(start);
+        }
+        self
+    }
+
+    /// Sets the threshold of the span, below which it is only aggregated, or
+    /// with `None`, reports it whatever the default threshold.
+    pub fn threshold(mut self, threshold: impl Into<Option<Duration>>) -> Self {
+        self.threshold = Some(threshold.into());
+        self
+    }
+
+    /// Makes a guard dropped without [`finish`](SpanGuard::finish) report
+    /// [`Outcome::Completed`] rather than [`Outcome::EarlyExit`], unless it is
+    /// dropped by a panic.
+    pub(crate) fn complete_on_drop(mut self) -> Self {
+        self.unfinished = Outcome::Completed;
+        self
+    }
+
+    /// How long the span has been open, or zero if it is disabled.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.map_or(Duration::ZERO, clock::elapsed)
+    }
+
+    /// Marks the block as having run to completion with `result`, and closes
+    /// the span.
+    pub fn finish<T>(mut self, result: T) -> T {
+        self.outcome = Some(Outcome::Completed);
+        result
+    }
+
+    /// Closes the span without reporting it or the spans nested in it that
+    /// are still held back.
+    pub(crate) fn cancel(self) {
+        let (id, enabled, tracked) = (self.id, self.start.is_some(), self.tracked);
+        std::mem::forget(self);
+        if !enabled {
+            return;
+        }
+        if tracked {
+            active::exit(id);
+        }
+        let due = STACK.try_with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let due = stack.prune();
+            match stack.frames.iter().rposition(|frame| frame.id == id) {
+                Some(index) => {
+                    let frame = stack.frames.remove(index);
+                    stack.buffered -= frame.records.len();
+                    if stack.frames.is_empty() {
+                        stack.streaming = false;
+                    }
+                }
+                None => orphan(id),
+            }
+            due
+        });
+        for record in due.iter().flatten() {
+            crate::sink::send(record);
+        }
+    }
+}
+
+impl Drop for SpanGuard {
+    /// Closes the span and dispatches it.
+    fn drop(&mut self) {
+        let start = match self.start {
+            Some(start) => start,
+            None => return,
+        };
+        let duration = clock::elapsed(start);
+        if self.tracked {
+            active::exit(self.id);
+        }
+        let mut record = TimingRecord::new(
+            self.tag,
+            self.file,
+            self.line,
+            self.column,
+            self.module,
+            start,
+            duration,
+        );
+        record.label = self.label.take();
+        record.fields = std::mem::take(&mut self.fields);
+        if let Some(threshold) = self.threshold {
+            record.threshold = threshold;
+        }
+        record.outcome = match self.outcome {
+            Some(outcome) => outcome,
+            None if thread::panicking() => Outcome::Panicked,
+            None => self.unfinished,
+        };
+
+        let due = STACK.try_with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let mut due = stack.prune();
+            // Spans are usually closed innermost first, but guards may be
+            // dropped in any order, so the frame is looked up by id.
+            let depth = match stack.frames.iter().rposition(|frame| frame.id == self.id) {
+                Some(depth) => depth,
+                None => {
+                    // The span was opened on another thread.
+                    orphan(self.id);
+                    crate::stats::aggregate(&record);
+                    if !record.is_below_threshold() {
+                        due.push(record);
+                    }
+                    return due;
+                }
+            };
+            let frame = stack.frames.remove(depth);
+            record.depth = depth;
+            record.self_time = duration.saturating_sub(frame.child_time);
+            crate::stats::aggregate(&record);
+            // A block hidden by its threshold is still reported when a block
+            // nested in it was, so that the nested one is not shown orphaned.
+            let shown = !record.is_below_threshold() || frame.nested_reported;
+
+            let mut records = Vec::with_capacity(frame.records.len() + 1);
+            if shown {
+                records.push(record);
+                stack.buffered += 1;
+            }
+            records.extend(frame.records);
+            if let Some(parent) = depth.checked_sub(1) {
+                let streaming = stack.streaming;
+                let parent = &mut stack.frames[parent];
+                parent.child_time += duration;
+                parent.nested_reported |= shown;
+                if !streaming {
+                    parent.records.extend(records);
+                    if stack.buffered > MAX_BUFFERED {
+                        stack.streaming = true;
+                        due.extend(stack.drain());
+                    }
+                    return due;
+                }
+            }
+            stack.buffered -= records.len();
+            due.extend(records);
+            if stack.frames.is_empty() {
+                stack.streaming = false;
+            }
+            due
+        });
+
+        // The stack is released before dispatching, so sinks may use `timer!`.
+        for record in due.iter().flatten() {
+            crate::sink::send(record);
+        }
+    }
+}
+
+/// Leaves the frame of span `id` for the thread that opened it to remove.
+fn orphan(id: u64) {
+    let mut orphans = orphans();
+    orphans.push(id);
+    HAS_ORPHANS.store(true, Ordering::Relaxed);
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "release_also")))]
+mod tests {
+    use super::MAX_BUFFERED;
+    use crate::sink::testing::{capture, DefaultThreshold};
+    use crate::{snapshot, timer, with_clock, FieldValue, MockClock, Outcome};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_nested_timers_form_a_tree() {
+        let (reported, records) = capture(|| {
+            timer!(# outer {
+                timer!(# inner {
+                    timer!(# leaf {
+                        thread::sleep(Duration::from_millis(2));
+                    });
+                });
+                timer!(# sibling {});
+                // Nested spans are aggregated as they close, but reported
+                // with the outermost one.
+                snapshot()
+                    .iter()
+                    .any(|s| s.tag == "leaf" && s.file == file!())
+            })
+        });
+        assert!(reported);
+
+        let tree: Vec<_> = records.iter().map(|r| (r.tag, r.depth)).collect();
+        assert_eq!(
+            tree,
+            [("outer", 0), ("inner", 1), ("leaf", 2), ("sibling", 1)]
+        );
+        let (outer, inner, leaf, sibling) = (&records[0], &records[1], &records[2], &records[3]);
+        assert_eq!(leaf.self_time, leaf.duration);
+        assert_eq!(inner.self_time, inner.duration - leaf.duration);
+        assert_eq!(
+            outer.self_time,
+            outer.duration - inner.duration - sibling.duration
+        );
+    }
+
+    #[test]
+    fn test_early_exits_are_reported() {
+        let (_, records) = capture(|| {
+            timer!(# outer {
+                let _ = (|| -> Option<()> {
+                    timer!(# question_mark {
+                        None?;
+                    });
+                    Some(())
+                })();
+                #[allow(clippy::never_loop)]
+                'outer: loop {
+                    timer!(# labeled_break {
+                        break 'outer;
+                    });
+                }
+                let _ = std::panic::catch_unwind(|| {
+                    timer!(# panicked {
+                        panic!("expected");
+                    });
+                });
+                timer!(# completed {});
+            });
+        });
+
+        let tree: Vec<_> = records
+            .iter()
+            .map(|r| (r.tag, r.depth, r.outcome))
+            .collect();
+        assert_eq!(
+            tree,
+            [
+                ("outer", 0, Outcome::Completed),
+                ("question_mark", 1, Outcome::EarlyExit),
+                ("labeled_break", 1, Outcome::EarlyExit),
+                ("panicked", 1, Outcome::Panicked),
+                ("completed", 1, Outcome::Completed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_formatted_tags_and_fields() {
+        let files = ["a.txt", "b.txt"];
+        let (_, records) = capture(|| {
+            for (index, file) in files.iter().enumerate() {
+                let name = file.to_string();
+                timer!(tag: "span load {}", file; fields: index = index, name = name, ok = true; block: {});
+                // Fields are borrowed, not moved.
+                assert_eq!(name, *file);
+            }
+            timer!(tag: "span plain"; block: {});
+        });
+
+        let labels: Vec<_> = records.iter().map(|r| (r.tag, r.name())).collect();
+        assert_eq!(
+            labels,
+            [
+                ("span load {}", "span load a.txt"),
+                ("span load {}", "span load b.txt"),
+                ("span plain", "span plain"),
+            ]
+        );
+        assert_eq!(records[2].label, None);
+        assert_eq!(
+            records[1].fields,
+            [
+                ("index", FieldValue::U64(1)),
+                ("name", FieldValue::Str("b.txt".to_string())),
+                ("ok", FieldValue::Bool(true)),
+            ]
+        );
+        let display = records[1].to_string();
+        assert!(display.contains(" span load b.txt [index=1, name=b.txt, ok=true]: "));
+
+        // Timings are aggregated per call site, not per formatted tag.
+        let stats = snapshot();
+        let load = stats.iter().find(|s| s.tag == "span load {}").unwrap();
+        assert_eq!(load.count, 2);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let clock = MockClock::new();
+        let advance = |millis| clock.advance(Duration::from_millis(millis));
+        let (_, records) = capture(|| {
+            with_clock(clock.clone(), || {
+                for millis in [1, 10] {
+                    timer!(tag: "span threshold", threshold: 5ms, block: {
+                        advance(millis);
+                    });
+                }
+                timer!(tag: "span {}", "fields"; threshold: 1.5ms; block: {
+                    advance(1);
+                });
+                // `capture` keeps other tests from seeing the default change.
+                let _default = DefaultThreshold::set(Some(Duration::from_millis(5)));
+                timer!(tag: "span default threshold", block: {
+                    advance(2);
+                });
+                timer!(tag: "span no threshold", threshold: None, block: {
+                    advance(2);
+                });
+                // Only timings slower than the threshold are reported.
+                timer!(tag: "span at threshold", threshold: 5ms, block: {
+                    advance(5);
+                });
+                // A fast block is still reported when a nested one was.
+                timer!(tag: "span fast parent", threshold: 10ms, block: {
+                    advance(1);
+                    timer!(tag: "span slow child", block: {
+                        advance(6);
+                    });
+                });
+            })
+        });
+
+        let tags: Vec<_> = records
+            .iter()
+            .map(|r| (r.tag, r.depth, r.is_slow()))
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("span threshold", 0, true),
+                ("span no threshold", 0, false),
+                ("span fast parent", 0, false),
+                ("span slow child", 1, true),
+            ]
+        );
+        assert_eq!(records[1].threshold, None);
+        let display = records[0].to_string();
+        assert!(display.starts_with("warning: in "));
+        assert!(display.ends_with(" span threshold: 10.0 ms (over 5.00 ms)"));
+        assert!(!records[2].to_string().contains("warning"));
+
+        // Timings below the threshold still count in the statistics.
+        let stats = snapshot();
+        let threshold = stats.iter().find(|s| s.tag == "span threshold").unwrap();
+        assert_eq!(threshold.count, 2);
+    }
+
+    /// Stays pending the first time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn poll<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::new(Noop));
+        future.poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn test_guard_is_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        async fn migrate() -> u32 {
+            timer!(# "span migrated" {
+                timer!(# "span before await" {});
+                YieldOnce(false).await;
+                1
+            })
+        }
+
+        let mut future = Box::pin(migrate());
+        assert_send(&future);
+        let (_, records) = capture(|| {
+            timer!(# "span host" {
+                assert!(poll(future.as_mut()).is_pending());
+                // The span closes on another thread, leaving its frame here.
+                let polled = thread::spawn(move || poll(future.as_mut()));
+                assert_eq!(polled.join().unwrap(), Poll::Ready(1));
+                timer!(# "span after migration" {});
+            });
+        });
+
+        // The spans held back for the migrated span move up to its parent.
+        let tree: Vec<_> = records.iter().map(|r| (r.tag, r.depth)).collect();
+        assert_eq!(
+            tree,
+            [
+                ("span host", 0),
+                ("span before await", 1),
+                ("span after migration", 1),
+            ]
+        );
+        let stats = snapshot();
+        let migrated = stats.iter().find(|s| s.tag == "span migrated").unwrap();
+        assert_eq!(migrated.count, 1);
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let (_, records) = capture(|| {
+            timer!(# "span busy outer" {
+                for _ in 0..MAX_BUFFERED + 2 {
+                    timer!(# "span busy inner" {});
+                }
+            });
+            timer!(# "span calm outer" {
+                timer!(# "span calm inner" {});
+            });
+        });
+
+        let tags: Vec<_> = records.iter().map(|r| r.tag).collect();
+        let count = MAX_BUFFERED + 2;
+        // Past the bound, the held records are reported, then each span as
+        // it closes, and the outermost one last.
+        assert_eq!(tags[..count], vec!["span busy inner"; count][..]);
+        assert_eq!(
+            tags[count..],
+            ["span busy outer", "span calm outer", "span calm inner"]
+        );
+    }
+}
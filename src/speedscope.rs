@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A sink writing timings as a speedscope profile.
+
+use crate::json::write_string;
+use crate::sink::OutputFile;
+use crate::{Sink, TimingRecord};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+/// Collects every record, and writes them as a [speedscope](https://www.speedscope.app)
+/// profile with one evented timeline per thread.
+///
+/// Frames are named after the `timer!` tags and carry the file and line of the
+/// timer, so speedscope can group the calls of each timer; tags with format
+/// arguments appear unformatted for the same reason. The timings are kept in
+/// memory, and the whole profile is written to the file whenever the sink is
+/// [flushed](crate::flush).
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{set_sink, SpeedscopeSink};
+///
+/// set_sink(SpeedscopeSink::new("profile.speedscope.json"));
+/// ```
+#[derive(Debug)]
+pub struct SpeedscopeSink {
+    file: OutputFile,
+    profile: Mutex<Profile>,
+}
+
+#[derive(Debug, Default)]
+struct Profile {
+    /// Distinct timers, as `(tag, file, line)`.
+    frames: Vec<(&'static str, &'static str, u32)>,
+    frame_index: HashMap<(&'static str, &'static str, u32), usize>,
+    /// The timings of each thread, in the order the threads were first seen.
+    threads: Vec<ThreadSpans>,
+    thread_index: HashMap<ThreadId, usize>,
+}
+
+#[derive(Debug)]
+struct ThreadSpans {
+    name: String,
+    /// `(frame, start, end)`, in nanoseconds.
+    spans: Vec<(usize, u128, u128)>,
+}
+
+impl SpeedscopeSink {
+    /// Creates a sink that writes its profile to the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: OutputFile::new(path.as_ref(), "speedscope profile"),
+            profile: Mutex::new(Profile::default()),
+        }
+    }
+
+    /// Writes the profile collected so far to `writer`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let profile = self
+            .profile
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut json = String::from(
+            "{\"$schema\":\"https://www.speedscope.app/file-format-schema.json\",\"shared\":{\"frames\":[",
+        );
+        for (index, (tag, file, line)) in profile.frames.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"name\":");
+            write_string(&mut json, tag);
+            json.push_str(",\"file\":");
+            write_string(&mut json, file);
+            let _ = write!(json, ",\"line\":{}}}", line);
+        }
+        json.push_str("]},\"profiles\":[");
+        for (index, thread) in profile.threads.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            write_thread(&mut json, thread);
+        }
+        let _ = write!(
+            json,
+            "],\"name\":\"quick-timer\",\"activeProfileIndex\":0,\"exporter\":\"quick-timer {}\"}}",
+            env!("CARGO_PKG_VERSION")
+        );
+        writer.write_all(json.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
+
+impl Sink for SpeedscopeSink {
+    fn record(&self, record: &TimingRecord) {
+        let mut profile = self
+            .profile
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let profile = &mut *profile;
+
+        let key = (record.tag, record.file, record.line);
+        let frames = &mut profile.frames;
+        let frame = *profile.frame_index.entry(key).or_insert_with(|| {
+            frames.push(key);
+            frames.len() - 1
+        });
+
+        let threads = &mut profile.threads;
+        let thread = *profile
+            .thread_index
+            .entry(record.thread.id())
+            .or_insert_with(|| {
+                let name = match record.thread.name() {
+                    Some(name) => name.to_string(),
+                    None => format!("{:?}", record.thread.id()),
+                };
+                threads.push(ThreadSpans {
+                    name,
+                    spans: Vec::new(),
+                });
+                threads.len() - 1
+            });
+
+        let start = record.start.as_nanos();
+        let end = start + record.duration.as_nanos();
+        threads[thread].spans.push((frame, start, end));
+    }
+
+    fn flush(&self) {
+        self.file.write(|writer| self.write_to(writer));
+    }
+}
+
+/// Appends the evented profile of one thread to `json`.
+fn write_thread(json: &mut String, thread: &ThreadSpans) {
+    // Parents start no later than their children, and enclose them, so
+    // sorting by start, longest first, puts every span after its parent.
+    let mut spans = thread.spans.clone();
+    spans.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)));
+    let start_value = spans.first().map_or(0, |span| span.1);
+    let end_value = spans.iter().map(|span| span.2).max().unwrap_or(0);
+
+    json.push_str("{\"type\":\"evented\",\"name\":");
+    write_string(json, &thread.name);
+    let _ = write!(
+        json,
+        ",\"unit\":\"nanoseconds\",\"startValue\":{},\"endValue\":{},\"events\":[",
+        start_value, end_value
+    );
+
+    let mut first = true;
+    let mut event = |json: &mut String, kind: char, frame: usize, at: u128| {
+        if !first {
+            json.push(',');
+        }
+        first = false;
+        let _ = write!(
+            json,
+            "{{\"type\":\"{}\",\"frame\":{},\"at\":{}}}",
+            kind, frame, at
+        );
+    };
+    // Open spans, as `(frame, end)`.
+    let mut open: Vec<(usize, u128)> = Vec::new();
+    for (frame, start, end) in spans {
+        while let Some(&(top, top_end)) = open.last() {
+            if top_end > start {
+                break;
+            }
+            event(json, 'C', top, top_end);
+            open.pop();
+        }
+        // Events must nest, so a span never outlives the span it starts in.
+        let end = open
+            .last()
+            .map_or(end, |&(_, parent_end)| end.min(parent_end));
+        event(json, 'O', frame, start);
+        open.push((frame, end));
+    }
+    while let Some((frame, end)) = open.pop() {
+        event(json, 'C', frame, end);
+    }
+    json.push_str("]}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_nest() {
+        let thread = ThreadSpans {
+            name: "main".to_string(),
+            // Recorded innermost first, with the leaf overrunning its parent.
+            spans: vec![(2, 20, 35), (1, 10, 30), (0, 0, 40), (1, 50, 60)],
+        };
+        let mut json = String::new();
+        write_thread(&mut json, &thread);
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"type":"evented","name":"main","unit":"nanoseconds","startValue":0,"endValue":60,"events":["#,
+                r#"{"type":"O","frame":0,"at":0},{"type":"O","frame":1,"at":10},{"type":"O","frame":2,"at":20},"#,
+                r#"{"type":"C","frame":2,"at":30},{"type":"C","frame":1,"at":30},{"type":"C","frame":0,"at":40},"#,
+                r#"{"type":"O","frame":1,"at":50},{"type":"C","frame":1,"at":60}]}"#
+            )
+        );
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Process-wide aggregation of timings per call site.
+
+use crate::{Histogram, HumanDuration, TimingRecord};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Aggregated statistics for one timer call site.
+///
+/// Returned by [`snapshot`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TimerStats {
+    /// The tag of the timer.
+    pub tag: &'static str,
+    /// The source file of the timer.
+    pub file: &'static str,
+    /// The source line of the timer.
+    pub line: u32,
+    /// The source column of the timer.
+    pub column: u32,
+    /// The module path of the timer.
+    pub module: &'static str,
+    /// How many timings were recorded.
+    pub count: u64,
+    /// The sum of all recorded durations.
+    pub total: Duration,
+    /// The part of `total` not spent in nested `timer!` blocks.
+    pub self_total: Duration,
+    /// The shortest recorded duration.
+    pub min: Duration,
+    /// The longest recorded duration.
+    pub max: Duration,
+    /// The distribution of the recorded durations.
+    pub histogram: Histogram,
+    mean_nanos: f64,
+    m2: f64,
+}
+
+impl TimerStats {
+    fn new(record: &TimingRecord) -> Self {
+        Self {
+            tag: record.tag,
+            file: record.file,
+            line: record.line,
+            column: record.column,
+            module: record.module,
+            count: 0,
+            total: Duration::ZERO,
+            self_total: Duration::ZERO,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            histogram: Histogram::new(),
+            mean_nanos: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    fn add(&mut self, duration: Duration, self_time: Duration) {
+        // Welford's online algorithm, so the variance stays accurate over
+        // millions of samples.
+        let nanos = duration.as_nanos() as f64;
+        self.count += 1;
+        self.total = self.total.saturating_add(duration);
+        self.self_total = self.self_total.saturating_add(self_time);
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.histogram.record(duration);
+        let delta = nanos - self.mean_nanos;
+        self.mean_nanos += delta / self.count as f64;
+        self.m2 += delta * (nanos - self.mean_nanos);
+    }
+
+    /// The mean recorded duration.
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos(self.mean_nanos as u64)
+    }
+
+    /// The population variance of the recorded durations, in nanoseconds squared.
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// The population standard deviation of the recorded durations.
+    pub fn std_dev(&self) -> Duration {
+        Duration::from_nanos(self.variance().sqrt() as u64)
+    }
+}
+
+type Key = (&'static str, &'static str, u32, u32);
+
+static REGISTRY: Mutex<Option<HashMap<Key, TimerStats>>> = Mutex::new(None);
+
+fn registry() -> MutexGuard<'static, Option<HashMap<Key, TimerStats>>> {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Adds `record` to the statistics of its call site.
+#[doc(hidden)]
+pub fn aggregate(record: &TimingRecord) {
+    let mut registry = registry();
+    registry
+        .get_or_insert_with(HashMap::new)
+        .entry((record.tag, record.file, record.line, record.column))
+        .or_insert_with(|| TimerStats::new(record))
+        .add(record.duration, record.self_time);
+}
+
+/// Returns the statistics of every call site that has recorded a timing,
+/// sorted by total time, longest first.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{snapshot, timer_silent};
+///
+/// // `timer_silent!` records in every profile, where `timer!` is disabled in
+/// // release builds without the `release_also` feature.
+/// for _ in 0..3 {
+///     timer_silent!(# "loop body" {
+///         println!("working");
+///     });
+/// }
+///
+/// let stats = snapshot();
+/// let body = stats.iter().find(|s| s.tag == "loop body").unwrap();
+/// assert_eq!(body.count, 3);
+/// ```
+pub fn snapshot() -> Vec<TimerStats> {
+    let mut stats: Vec<TimerStats> = registry()
+        .as_ref()
+        .map(|registry| registry.values().cloned().collect())
+        .unwrap_or_default();
+    stats.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| a.file.cmp(b.file))
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.column.cmp(&b.column))
+    });
+    stats
+}
+
+/// Returns the distribution of every duration recorded under `tag`, merged
+/// across all call sites using that tag, or `None` if there are none.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{histogram, timer_silent};
+///
+/// for _ in 0..100 {
+///     timer_silent!(# "parse" {
+///         "42".parse::<u32>().unwrap()
+///     });
+/// }
+///
+/// let parse = histogram("parse").unwrap();
+/// assert_eq!(parse.count(), 100);
+/// println!("p50 {:?}, p99 {:?}", parse.percentile(50.0), parse.percentile(99.0));
+/// ```
+pub fn histogram(tag: &str) -> Option<Histogram> {
+    let registry = registry();
+    let mut merged: Option<Histogram> = None;
+    for stats in registry
+        .iter()
+        .flat_map(|r| r.values())
+        .filter(|s| s.tag == tag)
+    {
+        match &mut merged {
+            Some(merged) => merged.merge(&stats.histogram),
+            None => merged = Some(stats.histogram.clone()),
+        }
+    }
+    merged
+}
+
+/// Clears all aggregated statistics.
+pub fn reset_stats() {
+    *registry() = None;
+}
+
+/// Prints a summary table of every call site to stdout, sorted by total time.
+pub fn report() {
+    let stdout = io::stdout();
+    let _ = write_report(&mut stdout.lock());
+}
+
+/// Writes the summary table printed by [`report`] to `out`.
+pub fn write_report<W: Write>(out: &mut W) -> io::Result<()> {
+    const HEADER: [&str; 13] = [
+        "tag", "location", "count", "total", "self", "mean", "min", "p50", "p90", "p99", "p99.9",
+        "max", "std dev",
+    ];
+
+    let rows: Vec<[String; 13]> = snapshot()
+        .iter()
+        .map(|s| {
+            let duration = |d: Duration| HumanDuration::new(d).to_string();
+            [
+                s.tag.to_string(),
+                format!("{}:{}", s.file, s.line),
+                s.count.to_string(),
+                duration(s.total),
+                duration(s.self_total),
+                duration(s.mean()),
+                duration(s.min),
+                duration(s.histogram.percentile(50.0)),
+                duration(s.histogram.percentile(90.0)),
+                duration(s.histogram.percentile(99.0)),
+                duration(s.histogram.percentile(99.9)),
+                duration(s.max),
+                duration(s.std_dev()),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    write_row(out, &widths, HEADER.iter())?;
+    for row in &rows {
+        write_row(out, &widths, row.iter())?;
+    }
+    Ok(())
+}
+
+/// Writes one table row: the tag and location columns are left-aligned, the
+/// numbers right-aligned.
+fn write_row<W: Write, S: AsRef<str>>(
+    out: &mut W,
+    widths: &[usize],
+    cells: impl Iterator<Item = S>,
+) -> io::Result<()> {
+    for (column, (cell, width)) in cells.zip(widths).enumerate() {
+        let cell = cell.as_ref();
+        match column {
+            0 => write!(out, "{:<width$}", cell, width = width)?,
+            1 => write!(out, "  {:<width$}", cell, width = width)?,
+            _ => write!(out, "  {:>width$}", cell, width = width)?,
+        }
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_welford() {
+        let (_, record) = crate::timer_silent!(# stats {});
+        let mut stats = TimerStats::new(&record);
+        for millis in [2, 4, 4, 4, 5, 5, 7, 9] {
+            stats.add(Duration::from_millis(millis), Duration::from_millis(millis));
+        }
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.total, Duration::from_millis(40));
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(stats.max, Duration::from_millis(9));
+        assert_eq!(stats.mean(), Duration::from_millis(5));
+        assert_eq!(stats.std_dev(), Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_stats_keyed_by_column() {
+        #[rustfmt::skip]
+        let (_, _) = (crate::timer_silent!(# "same line" {}), crate::timer_silent!(# "same line" {}));
+        let _ = crate::timer_silent! { 1 };
+        let stats: Vec<_> = snapshot()
+            .into_iter()
+            .filter(|stats| stats.file == file!())
+            .collect();
+        let same_line: Vec<_> = stats.iter().filter(|s| s.tag == "same line").collect();
+        assert_eq!(same_line.len(), 2);
+        assert_eq!(same_line[0].line, same_line[1].line);
+        assert_ne!(same_line[0].column, same_line[1].column);
+        assert!(stats.iter().all(|stats| stats.tag != "Timer"));
+    }
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Timing without macros: a manual stopwatch and a reporting guard.
+
+use crate::{clock, SpanGuard};
+use std::panic::Location;
+use std::time::{Duration, Instant};
+
+/// A stopwatch that can be started, paused and read at any point.
+///
+/// Unlike `timer!`, a stopwatch reports nothing by itself; it only measures.
+/// Use a [`TimerGuard`] to report a region whose start and end are in
+/// different scopes.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::Stopwatch;
+///
+/// let mut stopwatch = Stopwatch::start_new();
+/// for step in 0..3 {
+///     // do some work ...
+///     let lap = stopwatch.lap();
+///     println!("step {} took {:?}", step, lap);
+/// }
+///
+/// stopwatch.pause();
+/// // ... time spent here is not counted ...
+/// stopwatch.resume();
+///
+/// let total = stopwatch.stop();
+/// assert_eq!(stopwatch.laps().len(), 3);
+/// assert!(total >= stopwatch.laps().iter().sum());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Stopwatch {
+    /// Time accumulated before the current run.
+    accumulated: Duration,
+    /// When the current run started, if the stopwatch is running.
+    running_since: Option<Instant>,
+    /// The split time at the last lap.
+    last_lap: Duration,
+    laps: Vec<Duration>,
+}
+
+impl Stopwatch {
+    /// Creates a stopwatch that is not running and reads zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a stopwatch and starts it.
+    pub fn start_new() -> Self {
+        let mut stopwatch = Self::new();
+        stopwatch.start();
+        stopwatch
+    }
+
+    /// Resets the stopwatch to zero and starts it.
+    pub fn start(&mut self) {
+        self.reset();
+        self.running_since = Some(clock::now());
+    }
+
+    /// Stops the stopwatch and returns the total time it ran.
+    ///
+    /// A stopped stopwatch can be continued with [`resume`](Stopwatch::resume),
+    /// or restarted from zero with [`start`](Stopwatch::start).
+    pub fn stop(&mut self) -> Duration {
+        self.pause();
+        self.accumulated
+    }
+
+    /// Stops counting time, keeping the time measured so far.
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += clock::elapsed(since);
+        }
+    }
+
+    /// Continues counting time after [`pause`](Stopwatch::pause) or
+    /// [`stop`](Stopwatch::stop).
+    pub fn resume(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(clock::now());
+        }
+    }
+
+    /// Returns the time since the previous lap, or since the start for the
+    /// first lap, and records it in [`laps`](Stopwatch::laps).
+    pub fn lap(&mut self) -> Duration {
+        let split = self.split();
+        let lap = split.saturating_sub(self.last_lap);
+        self.last_lap = split;
+        self.laps.push(lap);
+        lap
+    }
+
+    /// Returns the total time so far without recording a lap. Same as
+    /// [`elapsed`](Stopwatch::elapsed).
+    pub fn split(&self) -> Duration {
+        self.elapsed()
+    }
+
+    /// Returns the total time the stopwatch has been running, excluding time
+    /// spent paused.
+    pub fn elapsed(&self) -> Duration {
+        match self.running_since {
+            Some(since) => self.accumulated + clock::elapsed(since),
+            None => self.accumulated,
+        }
+    }
+
+    /// The laps recorded with [`lap`](Stopwatch::lap), oldest first.
+    pub fn laps(&self) -> &[Duration] {
+        &self.laps
+    }
+
+    /// Whether the stopwatch is counting time.
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Resets the stopwatch to zero and clears its laps. A running stopwatch
+    /// keeps running.
+    pub fn reset(&mut self) {
+        self.accumulated = Duration::ZERO;
+        self.last_lap = Duration::ZERO;
+        self.laps.clear();
+        if self.running_since.is_some() {
+            self.running_since = Some(clock::now());
+        }
+    }
+}
+
+/// Times a region of code and reports it through the installed
+/// [`Sink`](crate::Sink) when dropped, the same way `timer!` does.
+///
+/// The guard takes part in nesting like a `timer!` block, so timers run while
+/// it is alive become its children. It reports [`Outcome::Completed`] when
+/// dropped or [`stop`](TimerGuard::stop)ped, or [`Outcome::Panicked`] when
+/// dropped by a panic. Unlike `timer!`, the guard is active in release builds
+/// too.
+///
+/// [`Outcome::Completed`]: crate::Outcome::Completed
+/// [`Outcome::Panicked`]: crate::Outcome::Panicked
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::TimerGuard;
+///
+/// fn handle(request: &str) -> usize {
+///     let guard = TimerGuard::new("handle");
+///     if request.is_empty() {
+///         // Don't report trivial requests
+///         guard.cancel();
+///         return 0;
+///     }
+///     let length = request.len();
+///     guard.stop();
+///     length
+/// }
+///
+/// handle("");
+/// handle("GET /");
+/// ```
+#[must_use = "the region is reported when the guard is dropped"]
+pub struct TimerGuard(SpanGuard);
+
+impl TimerGuard {
+    /// Starts timing a region tagged `tag`, attributed to the caller's
+    /// location. The module path of the record is left empty; use
+    /// [`with_module`](TimerGuard::with_module) to set it.
+    #[track_caller]
+    pub fn new(tag: &'static str) -> Self {
+        Self::with_module(tag, "")
+    }
+
+    /// Starts timing a region tagged `tag`, attributed to the caller's
+    /// location and to `module`. Pass `module_path!()` so that
+    /// [module directives](crate::set_filter) apply to the guard.
+    #[track_caller]
+    pub fn with_module(tag: &'static str, module: &'static str) -> Self {
+        let location = Location::caller();
+        Self(
+            SpanGuard::enter_uncached(
+                tag,
+                location.file(),
+                location.line(),
+                location.column(),
+                module,
+            )
+            .complete_on_drop(),
+        )
+    }
+
+    /// How long the region has been timed so far.
+    pub fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+
+    /// Stops timing and reports the region. Same as dropping the guard.
+    pub fn stop(self) {}
+
+    /// Stops timing without reporting the region, or the timers nested in it
+    /// whose records are still held back for it. The nested timers are still
+    /// counted in the [statistics](crate::snapshot), and those reported after
+    /// their thread held back too many records stay reported.
+    pub fn cancel(self) {
+        self.0.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::testing::capture;
+    use crate::{MockClock, Outcome};
+
+    #[test]
+    fn test_stopwatch_pause_excludes_time() {
+        let clock = MockClock::new();
+        crate::with_clock(clock.clone(), || {
+            let millis = Duration::from_millis;
+            let mut stopwatch = Stopwatch::start_new();
+            clock.advance(millis(5));
+            stopwatch.pause();
+            clock.advance(millis(100));
+            assert_eq!(stopwatch.elapsed(), millis(5));
+            assert!(!stopwatch.is_running());
+
+            stopwatch.resume();
+            clock.advance(millis(2));
+            assert_eq!(stopwatch.lap(), millis(7));
+            clock.advance(millis(3));
+            assert_eq!(stopwatch.lap(), millis(3));
+            assert_eq!(stopwatch.split(), millis(10));
+            assert_eq!(stopwatch.laps(), [millis(7), millis(3)]);
+
+            stopwatch.reset();
+            assert!(stopwatch.laps().is_empty());
+            assert!(stopwatch.is_running());
+            clock.advance(millis(1));
+            assert_eq!(stopwatch.stop(), millis(1));
+        });
+    }
+
+    #[test]
+    fn test_guards_report_out_of_order() {
+        let (_, records) = capture(|| {
+            let outer = TimerGuard::new("outer");
+            let inner = TimerGuard::with_module("inner", module_path!());
+            let cancelled = TimerGuard::new("cancelled");
+            cancelled.cancel();
+            // Dropping the outer guard first leaves `inner` as a root.
+            outer.stop();
+            inner.stop();
+        });
+
+        let tree: Vec<_> = records
+            .iter()
+            .map(|r| (r.tag, r.depth, r.outcome))
+            .collect();
+        assert_eq!(
+            tree,
+            [
+                ("outer", 0, Outcome::Completed),
+                ("inner", 0, Outcome::Completed),
+            ]
+        );
+        assert_eq!(records[0].file, file!());
+        assert_eq!(records[0].line + 1, records[1].line);
+        assert_eq!(records[0].module, "");
+        assert_eq!(records[1].module, module_path!());
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "release_also"))]
+    fn test_cancel_drops_held_nested_records() {
+        let (_, records) = capture(|| {
+            let guard = TimerGuard::new("cancelled outer");
+            crate::timer!(# "cancelled nested" {});
+            guard.cancel();
+        });
+
+        assert!(records.is_empty());
+        let stats = crate::snapshot();
+        assert!(stats.iter().any(|s| s.tag == "cancelled nested"));
+    }
+}
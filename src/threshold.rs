@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! Reporting only the timings slower than a threshold.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The environment variable the default threshold is read from.
+const THRESHOLD_ENV: &str = "QUICK_TIMER_MIN";
+
+/// The default threshold in nanoseconds, or one of the markers below.
+static THRESHOLD: AtomicU64 = AtomicU64::new(UNSET);
+/// The environment variable has not been read yet.
+const UNSET: u64 = u64::MAX;
+/// There is no default threshold.
+const NONE: u64 = u64::MAX - 1;
+
+/// Sets the default threshold of timers, below which their timings are
+/// aggregated into the [statistics](crate::report) but not reported to the
+/// [`Sink`](crate::Sink). Timings slower than the threshold are reported as
+/// warnings. `None` reports every timing, which is the default.
+///
+/// Until this is called, the threshold is read from the `QUICK_TIMER_MIN`
+/// environment variable the first time a timing is made, e.g.
+/// `QUICK_TIMER_MIN=5ms`. A `threshold:` given to `timer!` takes precedence
+/// over the default, and `threshold: None` reports every timing of a timer
+/// whatever the default.
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::{set_threshold, threshold};
+/// use std::time::Duration;
+///
+/// set_threshold(Some(Duration::from_millis(5)));
+/// assert_eq!(threshold(), Some(Duration::from_millis(5)));
+/// ```
+pub fn set_threshold(threshold: Option<Duration>) {
+    let nanos = threshold.map_or(NONE, |threshold| {
+        u64::try_from(threshold.as_nanos()).map_or(NONE - 1, |nanos| nanos.min(NONE - 1))
+    });
+    THRESHOLD.store(nanos, Ordering::Relaxed);
+}
+
+/// Returns the default threshold of timers.
+pub fn threshold() -> Option<Duration> {
+    match THRESHOLD.load(Ordering::Relaxed) {
+        UNSET => {
+            let threshold = std::env::var(THRESHOLD_ENV).ok().and_then(|value| {
+                let threshold = parse_duration(value.trim());
+                if threshold.is_none() && !value.trim().is_empty() {
+                    eprintln!(
+                        "quick-timer: ignoring invalid {} duration `{}`",
+                        THRESHOLD_ENV, value
+                    );
+                }
+                threshold
+            });
+            set_threshold(threshold);
+            threshold
+        }
+        NONE => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+/// Parses a duration such as `5ms`, `1.5s` or `250 us`.
+///
+/// The number may have a fraction, and must be followed by one of the units
+/// `ns`, `us`, `µs`, `ms` or `s`.
+pub(crate) const fn parse_duration(text: &str) -> Option<Duration> {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    let mut whole: u128 = 0;
+    let mut digits = 0;
+    while index < bytes.len() && bytes[index].is_ascii_digit() {
+        whole = whole * 10 + (bytes[index] - b'0') as u128;
+        if whole > u64::MAX as u128 {
+            return None;
+        }
+        index += 1;
+        digits += 1;
+    }
+
+    // The fraction, to nanosecond precision at most.
+    let mut fraction: u128 = 0;
+    let mut scale: u128 = 1;
+    if index < bytes.len() && bytes[index] == b'.' {
+        index += 1;
+        while index < bytes.len() && bytes[index].is_ascii_digit() {
+            if scale < 1_000_000_000 {
+                fraction = fraction * 10 + (bytes[index] - b'0') as u128;
+                scale *= 10;
+            }
+            index += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return None;
+    }
+    while index < bytes.len() && bytes[index] == b' ' {
+        index += 1;
+    }
+
+    let unit = match unit_nanos(bytes, index) {
+        Some(unit) => unit,
+        None => return None,
+    };
+    let nanos = whole * unit + fraction * unit / scale;
+    if nanos > u64::MAX as u128 {
+        return None;
+    }
+    Some(Duration::from_nanos(nanos as u64))
+}
+
+/// The length in nanoseconds of the unit that `bytes` ends with from `index`.
+const fn unit_nanos(bytes: &[u8], index: usize) -> Option<u128> {
+    let rest = bytes.len() - index;
+    if rest == 1 && bytes[index] == b's' {
+        return Some(1_000_000_000);
+    }
+    if rest == 2 && bytes[index + 1] == b's' {
+        return match bytes[index] {
+            b'n' => Some(1),
+            b'u' => Some(1_000),
+            b'm' => Some(1_000_000),
+            _ => None,
+        };
+    }
+    // `µs`, with either the micro sign or the Greek letter mu.
+    if rest == 3
+        && bytes[index + 2] == b's'
+        && ((bytes[index] == 0xc2 && bytes[index + 1] == 0xb5)
+            || (bytes[index] == 0xce && bytes[index + 1] == 0xbc))
+    {
+        return Some(1_000);
+    }
+    None
+}
+
+/// Parses the duration literal given as `threshold:` to `timer!`, failing to
+/// compile if it is invalid.
+#[doc(hidden)]
+pub const fn duration_literal(literal: &str) -> Duration {
+    match parse_duration(literal) {
+        Some(duration) => duration,
+        None => panic!("invalid duration, expected e.g. `5ms`, `1.5s` or `250us`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5ms"), Some(Duration::from_millis(5)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("250 us"), Some(Duration::from_micros(250)));
+        assert_eq!(parse_duration("2µs"), Some(Duration::from_micros(2)));
+        assert_eq!(
+            parse_duration("0.000000001s"),
+            Some(Duration::from_nanos(1))
+        );
+        assert_eq!(parse_duration(".5ms"), Some(Duration::from_micros(500)));
+        assert_eq!(parse_duration("7ns"), Some(Duration::from_nanos(7)));
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("ms"), None);
+        assert_eq!(parse_duration("5 minutes"), None);
+        assert_eq!(parse_duration("99999999999999999999s"), None);
+        const LITERAL: Duration = duration_literal("20ms");
+        assert_eq!(LITERAL, Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A sink writing timings in the Chrome Trace Event Format.
+
+use crate::json::{write_fields, write_string};
+use crate::sink::OutputFile;
+use crate::{Outcome, Sink, TimingRecord};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// Collects every record as a trace event, and writes them to a file that can
+/// be opened in `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+///
+/// Each timing becomes a complete (`"X"`) event on the track of the thread it
+/// ran on, so nested timers show up nested. Events are named after the
+/// formatted tag, and carry the fields of the timer in their arguments. Events
+/// are kept in memory, and the whole trace is written out whenever the sink is
+/// [flushed](crate::flush), including when the process exits.
+///
+/// # Examples
+///
+/// ```no_run
+/// use quick_timer::{flush, set_sink, timer, ChromeTraceSink};
+///
+/// set_sink(ChromeTraceSink::new("trace.json"));
+/// timer!(# "work" {
+///     // ...
+/// });
+/// // Writes `trace.json` now; it is also written when the process exits.
+/// flush();
+/// ```
+#[derive(Debug)]
+pub struct ChromeTraceSink {
+    file: OutputFile,
+    trace: Mutex<Trace>,
+}
+
+#[derive(Debug, Default)]
+struct Trace {
+    /// Encoded events, in the order they were recorded.
+    events: Vec<String>,
+    /// Small sequential ids for the threads seen so far.
+    threads: HashMap<ThreadId, u64>,
+}
+
+impl ChromeTraceSink {
+    /// Creates a sink that writes its trace to the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            file: OutputFile::new(path.as_ref(), "trace"),
+            trace: Mutex::new(Trace::default()),
+        }
+    }
+
+    /// Writes the trace collected so far to `writer`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let trace = self
+            .trace
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        writer.write_all(b"{\"traceEvents\":[\n")?;
+        for (index, event) in trace.events.iter().enumerate() {
+            if index > 0 {
+                writer.write_all(b",\n")?;
+            }
+            writer.write_all(event.as_bytes())?;
+        }
+        writer.write_all(b"\n],\"displayTimeUnit\":\"ns\"}\n")?;
+        writer.flush()
+    }
+}
+
+impl Sink for ChromeTraceSink {
+    fn record(&self, record: &TimingRecord) {
+        let mut trace = self
+            .trace
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let thread = record.thread.id();
+        let tid = match trace.threads.get(&thread) {
+            Some(&tid) => tid,
+            None => {
+                let tid = trace.threads.len() as u64 + 1;
+                trace.threads.insert(thread, tid);
+                if let Some(name) = record.thread.name() {
+                    let event = thread_name_event(tid, name);
+                    trace.events.push(event);
+                }
+                tid
+            }
+        };
+        let event = complete_event(tid, record);
+        trace.events.push(event);
+    }
+
+    fn flush(&self) {
+        self.file.write(|writer| self.write_to(writer));
+    }
+}
+
+/// Encodes `record` as a complete event on thread `tid`.
+fn complete_event(tid: u64, record: &TimingRecord) -> String {
+    let mut event = String::with_capacity(192);
+    event.push_str("{\"name\":");
+    write_string(&mut event, record.name());
+    event.push_str(",\"cat\":");
+    write_string(&mut event, record.module);
+    let _ = write!(
+        event,
+        ",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":{},\"tid\":{},\"args\":{{\"file\":",
+        Micros(record.start),
+        Micros(record.duration),
+        std::process::id(),
+        tid
+    );
+    write_string(&mut event, record.file);
+    let _ = write!(
+        event,
+        ",\"line\":{},\"self_ns\":{}",
+        record.line,
+        record.self_time.as_nanos()
+    );
+    match record.outcome {
+        Outcome::Completed => {}
+        Outcome::EarlyExit => event.push_str(",\"outcome\":\"early_exit\""),
+        Outcome::Panicked => event.push_str(",\"outcome\":\"panicked\""),
+        Outcome::Cancelled => event.push_str(",\"outcome\":\"cancelled\""),
+    }
+    if !record.fields.is_empty() {
+        event.push(',');
+        write_fields(&mut event, &record.fields);
+    }
+    event.push_str("}}");
+    event
+}
+
+/// Encodes a metadata event naming thread `tid`.
+fn thread_name_event(tid: u64, name: &str) -> String {
+    let mut event = format!(
+        "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":{},\"args\":{{\"name\":",
+        std::process::id(),
+        tid
+    );
+    write_string(&mut event, name);
+    event.push_str("}}");
+    event
+}
+
+/// Formats a duration in microseconds, the unit of trace timestamps, keeping
+/// nanosecond precision.
+struct Micros(Duration);
+
+impl std::fmt::Display for Micros {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nanos = self.0.as_nanos();
+        write!(f, "{}.{:03}", nanos / 1000, nanos % 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_trace_events() {
+        let sink = ChromeTraceSink::new("unused.json");
+        let start = Instant::now();
+        let mut outer = TimingRecord::new(
+            "outer",
+            "src/lib.rs",
+            1,
+            1,
+            "app",
+            start,
+            Duration::from_nanos(2_500),
+        );
+        outer.self_time = Duration::from_nanos(1_000);
+        let mut inner = TimingRecord::new(
+            "inner",
+            "src/lib.rs",
+            2,
+            5,
+            "app",
+            start,
+            Duration::from_nanos(1_500),
+        );
+        inner.depth = 1;
+        sink.record(&outer);
+        sink.record(&inner);
+
+        let mut output = Vec::new();
+        sink.write_to(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("{\"traceEvents\":["));
+        assert!(output.trim_end().ends_with("],\"displayTimeUnit\":\"ns\"}"));
+        // The test thread is named, so it gets a metadata event first.
+        assert_eq!(output.matches("\"ph\":\"M\"").count(), 1);
+        assert_eq!(output.matches("\"ph\":\"X\"").count(), 2);
+        assert!(output.contains("\"name\":\"outer\",\"cat\":\"app\",\"ph\":\"X\""));
+        assert!(output.contains(",\"dur\":2.500,"));
+        assert!(output.contains(",\"dur\":1.500,"));
+        assert!(output
+            .contains("\"tid\":1,\"args\":{\"file\":\"src/lib.rs\",\"line\":1,\"self_ns\":1000}"));
+    }
+
+    #[test]
+    fn test_micros() {
+        assert_eq!(
+            Micros(Duration::from_nanos(1_234_567)).to_string(),
+            "1234.567"
+        );
+        assert_eq!(Micros(Duration::from_nanos(5)).to_string(), "0.005");
+    }
+}
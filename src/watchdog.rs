@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+//! A background thread warning about `timer!` blocks that run for too long.
+
+use crate::{active, ActiveTimer, HumanDuration};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::Duration;
+
+/// The settings of a watchdog, which warns on stderr about `timer!` blocks
+/// that are still running after a while.
+///
+/// A `timer!` block is only reported once it finishes, so a block that hangs
+/// is never reported. The watchdog watches the blocks that are running on
+/// every thread, and warns about each one when it has been running for the
+/// [`after`](Watchdog::after) time, then again after every
+/// [`interval`](Watchdog::interval):
+///
+/// ```text
+/// warning: in src/main.rs line 12 sync: still running after 5.00 s (thread worker-2)
+/// ```
+///
+/// The watchdog reads the time through the global [clock](crate::set_clock).
+///
+/// # Examples
+///
+/// ```
+/// use quick_timer::Watchdog;
+/// use std::time::Duration;
+///
+/// let watchdog = Watchdog::new()
+///     .after(Duration::from_secs(10))
+///     .interval(Duration::from_secs(30))
+///     .start();
+/// // ... long-running work ...
+/// watchdog.stop();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchdog {
+    after: Duration,
+    interval: Duration,
+}
+
+impl Watchdog {
+    /// Creates a watchdog that warns about blocks running for 1 s, and again
+    /// every 5 s they keep running.
+    pub fn new() -> Self {
+        Self {
+            after: Duration::from_secs(1),
+            interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets how long a block runs before the first warning about it.
+    pub fn after(mut self, after: Duration) -> Self {
+        self.after = after;
+        self
+    }
+
+    /// Sets how often the warning about a block that keeps running is repeated.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Starts watching on a background thread, until
+    /// [`WatchdogHandle::stop`] is called.
+    ///
+    /// This starts [tracking](crate::track_active) the running blocks, so
+    /// only the blocks entered after it are watched.
+    pub fn start(self) -> WatchdogHandle {
+        crate::track_active();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        // Checking a few times per period keeps the warnings close to on time.
+        let tick = (self.after.min(self.interval) / 4).max(Duration::from_millis(1));
+        let thread = thread::Builder::new()
+            .name("quick-timer-watchdog".to_string())
+            .spawn(move || {
+                let mut warned = HashMap::new();
+                loop {
+                    thread::park_timeout(tick);
+                    if stopped.load(Ordering::Acquire) {
+                        break;
+                    }
+                    for warning in self.check(&active(), &mut warned) {
+                        eprintln!("{}", warning);
+                    }
+                }
+            })
+            .expect("failed to spawn the watchdog thread");
+        WatchdogHandle { stop, thread }
+    }
+
+    /// Returns the warnings due for the `active` timers. `warned` counts the
+    /// warnings given about each timer so far.
+    fn check(
+        &self,
+        active: &[ActiveTimer],
+        warned: &mut HashMap<(ThreadId, u64), u128>,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for timer in active {
+            if timer.elapsed < self.after {
+                continue;
+            }
+            let due = 1 + (timer.elapsed - self.after).as_nanos() / self.interval.as_nanos();
+            let count = warned.entry((timer.thread.id(), timer.id)).or_insert(0);
+            if due > *count {
+                *count = due;
+                warnings.push(warning(timer));
+            }
+        }
+        // Forget the timers that finished.
+        warned.retain(|&(thread, id), _| {
+            active
+                .iter()
+                .any(|timer| timer.thread.id() == thread && timer.id == id)
+        });
+        warnings
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats a warning as `warning: in FILE line N TAG: still running after X
+/// (thread NAME)`.
+fn warning(timer: &ActiveTimer) -> String {
+    format!(
+        "warning: in {} line {} {}: still running after {} (thread {})",
+        timer.file,
+        timer.line,
+        timer.name(),
+        HumanDuration::new(timer.elapsed),
+        timer.thread_name()
+    )
+}
+
+/// A running watchdog, started by [`Watchdog::start`].
+///
+/// Dropping the handle leaves the watchdog running until the process exits.
+#[derive(Debug)]
+pub struct WatchdogHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl WatchdogHandle {
+    /// Stops the watchdog, and waits for its thread to finish.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::Release);
+        self.thread.thread().unpark();
+        let _ = self.thread.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_warnings_repeat_at_intervals() {
+        let watchdog = Watchdog::new()
+            .after(Duration::from_secs(2))
+            .interval(Duration::from_secs(3));
+        let timer = |id, label: Option<&str>, secs| ActiveTimer {
+            tag: "sync",
+            label: label.map(str::to_string),
+            file: "src/main.rs",
+            line: 12,
+            column: 5,
+            module: "app",
+            thread: thread::current(),
+            depth: 0,
+            start: Instant::now(),
+            elapsed: Duration::from_secs(secs),
+            id,
+        };
+        let active = |secs| vec![timer(0, None, secs), timer(1, Some("sync 7"), secs)];
+        let mut warned = HashMap::new();
+
+        assert!(watchdog.check(&active(1), &mut warned).is_empty());
+        let warnings = watchdog.check(&active(2), &mut warned);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with(
+            "warning: in src/main.rs line 12 sync: still running after 2.00 s (thread "
+        ));
+        assert!(warnings[1].contains(" sync 7: "));
+        assert!(watchdog.check(&active(4), &mut warned).is_empty());
+        assert_eq!(watchdog.check(&active(5), &mut warned).len(), 2);
+        // A long pause warns once, not once per missed interval.
+        let warnings = watchdog.check(&active(20)[..1], &mut warned);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warned.len(), 1);
+    }
+}
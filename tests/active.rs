@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+#![cfg(any(debug_assertions, feature = "release_also"))]
+
+use quick_timer::{active, timer, track_active};
+
+// Tracking is global, so this runs in its own test binary, where nothing else
+// starts it.
+#[test]
+fn active_lists_nothing_until_tracking_starts() {
+    timer!(# "untracked" {
+        assert!(active().is_empty());
+    });
+    timer!(# "still untracked" {
+        assert!(active().is_empty());
+    });
+
+    track_active();
+    timer!(# "tracked" {
+        let tags: Vec<_> = active().into_iter().map(|timer| timer.tag).collect();
+        assert_eq!(tags, ["tracked"]);
+    });
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Copyright 2025 yyxxryrx.
+#![cfg(any(debug_assertions, feature = "release_also"))]
+
+use quick_timer::{set_sink, timed, Sink, TimingRecord};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+struct Capture(Mutex<Vec<TimingRecord>>);
+
+impl Sink for Capture {
+    fn record(&self, record: &TimingRecord) {
+        self.0.lock().unwrap().push(record.clone());
+    }
+}
+
+#[timed]
+fn free_function(x: u32) -> u32 {
+    x + 1
+}
+
+#[timed("custom tag")]
+fn tagged(x: u32) -> Result<u32, String> {
+    if x == 0 {
+        return Err("zero".to_string());
+    }
+    Ok(x)
+}
+
+#[timed(r#"raw "tag""#)]
+fn raw_tagged() {}
+
+#[timed]
+async fn async_function(x: u32) -> u32 {
+    std::future::ready(x).await * 2
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            return output;
+        }
+    }
+}
+
+struct Counter(u32);
+
+#[timed]
+impl Counter {
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    #[timed("bump")]
+    fn bump(&mut self) {
+        self.0 += 1;
+    }
+}
+
+impl Counter {
+    // Without the `impl` block, the default tag has no type name.
+    #[timed]
+    fn method(&self) -> u32 {
+        self.0 * 2
+    }
+}
+
+#[timed]
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[test]
+fn test_timed() {
+    let capture = Arc::new(Capture(Mutex::new(Vec::new())));
+    set_sink(capture.clone());
+
+    assert_eq!(free_function(1), 2);
+    assert_eq!(tagged(3), Ok(3));
+    raw_tagged();
+    let mut counter = Counter(1);
+    counter.bump();
+    assert_eq!(counter.get(), 2);
+    assert_eq!(counter.method(), 4);
+    assert_eq!(counter.to_string(), "2");
+    assert_eq!(block_on(async_function(2)), 4);
+
+    let records = capture.0.lock().unwrap();
+    let tags: Vec<_> = records
+        .iter()
+        .filter(|r| r.thread.id() == thread::current().id())
+        .map(|r| r.tag)
+        .collect();
+    assert_eq!(
+        tags,
+        [
+            "timed::free_function",
+            "custom tag",
+            "raw \"tag\"",
+            "bump",
+            "timed::Counter::get",
+            "timed::method",
+            "timed::Counter::fmt",
+            "timed::async_function",
+        ]
+    );
+}